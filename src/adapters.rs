@@ -5,7 +5,12 @@
 //! - find_patterns: Multi-pattern matching for adapter detection
 //! - trim_start/trim_end: Fixed-position trimming based on adapter positions
 //! - FastqStream: Streaming I/O for constant memory usage
+//!
+//! The same adapter motif set drives the FastQC-style "adapter content" curve
+//! reported by quality-stats (`AdapterContentAccumulator`).
 
+use crate::distributions::{LengthDistribution, LengthDistributionAccumulator};
+use crate::trimming::{LengthRuleStats, LengthRules};
 use crate::QcStatsMarker;
 use anyhow::Result;
 use biometal::alignment::{MotifFinder, MotifPattern, MotifMatch};
@@ -15,6 +20,7 @@ use biometal::FastqRecord;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AdapterStats {
@@ -23,6 +29,12 @@ pub struct AdapterStats {
     pub adapters_found: HashMap<String, usize>,
     pub total_bases_trimmed: usize,
     pub average_trim_length: f64,
+    #[serde(default)]
+    pub length_rules: LengthRuleStats,
+    #[serde(default)]
+    pub input_length_distribution: LengthDistribution,
+    #[serde(default)]
+    pub output_length_distribution: LengthDistribution,
 }
 
 impl Default for AdapterStats {
@@ -33,17 +45,149 @@ impl Default for AdapterStats {
             adapters_found: HashMap::new(),
             total_bases_trimmed: 0,
             average_trim_length: 0.0,
+            length_rules: LengthRuleStats::default(),
+            input_length_distribution: LengthDistribution::default(),
+            output_length_distribution: LengthDistribution::default(),
         }
     }
 }
 
 impl QcStatsMarker for AdapterStats {}
 
+/// Illumina adapter sequences (same set as AdapterDetector::new_illumina)
+pub fn illumina_adapters() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("Illumina Universal", "AGATCGGAAGAGCACACGTCTGAACTCCAGTCA"),
+        ("Illumina Small RNA 3'", "AGATCGGAAGAGCGTCGTGTAGGGAAAGAGTGT"),
+        ("Illumina Small RNA 5'", "TGGAATTCTCGGGTGCCAAGG"),
+    ]
+}
+
+/// Motif finder over `illumina_adapters`, at AdapterDetector stringency
+pub fn adapter_motif_finder() -> MotifFinder {
+    let patterns = illumina_adapters()
+        .into_iter()
+        .map(|(name, sequence)| MotifPattern::new(sequence, name))
+        .collect();
+    MotifFinder::new(patterns, 60) // High stringency like AdapterDetector
+}
+
+/// Cumulative percentage of reads containing one adapter at or before each
+/// position (FastQC "adapter content")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdapterContent {
+    pub adapter: String,
+    pub percent_by_position: Vec<f64>, // Index 0 = position 1
+    pub max_percent: f64,              // Value at the last position
+}
+
+/// Streaming per-position adapter start counts
+///
+/// Each read counts once per adapter, at the earliest position that adapter
+/// starts, so the cumulative curve is the share of reads already read into it.
+#[derive(Clone)]
+pub struct AdapterContentAccumulator {
+    finder: Arc<MotifFinder>,
+    adapters: Vec<&'static str>,
+    first_positions: Vec<Vec<u64>>, // Per adapter, reads whose first match starts at each position
+    reads: u64,
+    max_length: usize,
+}
+
+impl std::fmt::Debug for AdapterContentAccumulator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdapterContentAccumulator")
+            .field("adapters", &self.adapters)
+            .field("reads", &self.reads)
+            .field("max_length", &self.max_length)
+            .finish()
+    }
+}
+
+impl Default for AdapterContentAccumulator {
+    fn default() -> Self {
+        let adapters: Vec<&'static str> = illumina_adapters().into_iter().map(|(name, _)| name).collect();
+        Self {
+            finder: Arc::new(adapter_motif_finder()),
+            first_positions: vec![Vec::new(); adapters.len()],
+            adapters,
+            reads: 0,
+            max_length: 0,
+        }
+    }
+}
+
+impl AdapterContentAccumulator {
+    /// Scan one read for every adapter
+    pub fn add(&mut self, record: &FastqRecord) {
+        self.reads += 1;
+        self.max_length = self.max_length.max(record.sequence.len());
+
+        let mut earliest: Vec<Option<usize>> = vec![None; self.adapters.len()];
+        for adapter_match in self.finder.find_in_sequence(&record.id, &record.sequence) {
+            if let Some(index) = self.adapters.iter().position(|&name| name == adapter_match.motif_name) {
+                let first = earliest[index].get_or_insert(adapter_match.position);
+                *first = (*first).min(adapter_match.position);
+            }
+        }
+
+        for (positions, first) in self.first_positions.iter_mut().zip(earliest) {
+            if let Some(position) = first {
+                if position >= positions.len() {
+                    positions.resize(position + 1, 0);
+                }
+                positions[position] += 1;
+            }
+        }
+    }
+
+    /// Fold in counts from another set of reads
+    pub fn merge(&mut self, other: Self) {
+        self.reads += other.reads;
+        self.max_length = self.max_length.max(other.max_length);
+        for (positions, other) in self.first_positions.iter_mut().zip(other.first_positions) {
+            if other.len() > positions.len() {
+                positions.resize(other.len(), 0);
+            }
+            for (count, other) in positions.iter_mut().zip(other) {
+                *count += other;
+            }
+        }
+    }
+
+    /// Cumulative curves up to the longest read, one per adapter
+    pub fn finish(&self) -> Vec<AdapterContent> {
+        if self.reads == 0 {
+            return Vec::new();
+        }
+
+        self.adapters
+            .iter()
+            .zip(&self.first_positions)
+            .map(|(&adapter, positions)| {
+                let mut cumulative = 0u64;
+                let percent_by_position: Vec<f64> = (0..self.max_length)
+                    .map(|position| {
+                        cumulative += positions.get(position).copied().unwrap_or(0);
+                        cumulative as f64 / self.reads as f64 * 100.0
+                    })
+                    .collect();
+                AdapterContent {
+                    adapter: adapter.to_string(),
+                    max_percent: percent_by_position.last().copied().unwrap_or(0.0),
+                    percent_by_position,
+                }
+            })
+            .collect()
+    }
+}
+
 /// Adapter trimmer using biometal primitives
 pub struct AdapterTrimmer {
     pub min_adapter_length: usize,
     pub min_overlap: usize,
     pub trim_both_ends: bool,
+    pub length_rules: LengthRules, // Crops before adapter search, limits on the trimmed read
 }
 
 impl Default for AdapterTrimmer {
@@ -52,6 +196,7 @@ impl Default for AdapterTrimmer {
             min_adapter_length: 8,   // Minimum adapter match length
             min_overlap: 5,          // Minimum overlap to consider for trimming
             trim_both_ends: true,    // Check both 5' and 3' ends
+            length_rules: LengthRules::default(),
         }
     }
 }
@@ -63,6 +208,7 @@ impl AdapterTrimmer {
             min_adapter_length,
             min_overlap,
             trim_both_ends,
+            length_rules: LengthRules::default(),
         }
     }
 
@@ -73,14 +219,11 @@ impl AdapterTrimmer {
         output_path: Option<P>,
     ) -> Result<AdapterStats> {
         // Create motif finder with Illumina adapters (same as AdapterDetector::new_illumina)
-        let patterns = vec![
-            MotifPattern::new("AGATCGGAAGAGCACACGTCTGAACTCCAGTCA", "Illumina Universal"),
-            MotifPattern::new("AGATCGGAAGAGCGTCGTGTAGGGAAAGAGTGT", "Illumina Small RNA 3'"),
-            MotifPattern::new("TGGAATTCTCGGGTGCCAAGG", "Illumina Small RNA 5'"),
-        ];
-        let motif_finder = MotifFinder::new(patterns, 60); // High stringency like AdapterDetector
+        let motif_finder = adapter_motif_finder();
 
         let mut stats = AdapterStats::default();
+        let mut input_lengths = LengthDistributionAccumulator::default();
+        let mut output_lengths = LengthDistributionAccumulator::default();
 
         let input_path_ref = input_path.as_ref();
         let data_source = DataSource::from_path(input_path_ref);
@@ -97,6 +240,8 @@ impl AdapterTrimmer {
         for record_result in fastq_stream {
             let record = record_result?;
             stats.total_reads += 1;
+            input_lengths.add(record.sequence.len());
+            let record = self.length_rules.crop_record(record, &mut stats.length_rules);
 
             // Find adapters in this record
             let matches = motif_finder.find_in_sequence(
@@ -141,6 +286,10 @@ impl AdapterTrimmer {
             if was_trimmed {
                 stats.total_bases_trimmed += bases_trimmed_this_read;
             }
+            if self.length_rules.check(trimmed_record.sequence.len(), &mut stats.length_rules).is_some() {
+                continue;
+            }
+            output_lengths.add(trimmed_record.sequence.len());
 
             // Write record immediately if output is requested (streaming)
             if let Some(ref mut w) = writer {
@@ -152,6 +301,8 @@ impl AdapterTrimmer {
         if stats.reads_with_adapters > 0 {
             stats.average_trim_length = stats.total_bases_trimmed as f64 / stats.reads_with_adapters as f64;
         }
+        stats.input_length_distribution = input_lengths.finish();
+        stats.output_length_distribution = output_lengths.finish();
 
         // Finalize output stream if opened
         if let Some(w) = writer {
@@ -204,4 +355,25 @@ mod tests {
         assert_eq!(trimmer.min_overlap, 3);
         assert!(!trimmer.trim_both_ends);
     }
+
+    #[test]
+    fn test_adapter_content_is_cumulative() {
+        let universal = illumina_adapters()[0].1;
+        let read = |sequence: String| FastqRecord::new("r".to_string(), sequence.clone().into_bytes(), vec![b'I'; sequence.len()]);
+
+        let mut content = AdapterContentAccumulator::default();
+        content.add(&read(format!("{}{}", "ACGT".repeat(5), universal)));
+        content.add(&read(format!("{}{}", "ACGT".repeat(10), universal)));
+        content.add(&read("ACGT".repeat(18)));
+        content.add(&read("ACGT".repeat(18)));
+
+        let curves = content.finish();
+        let universal = &curves[0].percent_by_position;
+        assert_eq!(universal.len(), 73);
+        assert_eq!(universal[19], 0.0);
+        assert_eq!(universal[20], 25.0);
+        assert_eq!(universal[40], 50.0);
+        assert_eq!(curves[0].max_percent, 50.0);
+        assert_eq!(curves[2].max_percent, 0.0);
+    }
 }
\ No newline at end of file
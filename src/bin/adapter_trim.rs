@@ -5,6 +5,8 @@
 
 use anyhow::Result;
 use biometal_qc_tools::adapters::AdapterTrimmer;
+use biometal_qc_tools::trimming::LengthRules;
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
 use clap::{Arg, Command};
 use serde_json;
 use std::path::PathBuf;
@@ -52,6 +54,31 @@ fn main() -> Result<()> {
                 .help("Minimum overlap length to trigger trimming")
                 .default_value("5"),
         )
+        .arg(
+            Arg::new("min_length")
+                .long("min-length")
+                .value_name("LENGTH")
+                .help("Discard reads shorter than this after trimming")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("max_length")
+                .long("max-length")
+                .value_name("LENGTH")
+                .help("Discard reads longer than this after trimming"),
+        )
+        .arg(
+            Arg::new("headcrop")
+                .long("headcrop")
+                .value_name("BASES")
+                .help("Remove this many bases from the 5' end before any other trimming (Trimmomatic HEADCROP)"),
+        )
+        .arg(
+            Arg::new("crop")
+                .long("crop")
+                .value_name("BASES")
+                .help("Then keep at most this many bases from the 5' end (Trimmomatic CROP)"),
+        )
         .arg(
             Arg::new("trim_3_only")
                 .long("trim-3-only")
@@ -65,14 +92,25 @@ fn main() -> Result<()> {
                 .help("Verbose output")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_path = PathBuf::from(matches.get_one::<String>("input").unwrap());
     let output_path = matches.get_one::<String>("output").map(PathBuf::from);
     let stats_path = PathBuf::from(matches.get_one::<String>("stats").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let min_adapter_length: usize = matches.get_one::<String>("min_adapter_length").unwrap().parse()?;
     let min_overlap: usize = matches.get_one::<String>("min_overlap").unwrap().parse()?;
+    let min_length: usize = matches.get_one::<String>("min_length").unwrap().parse()?;
+    let max_length: Option<usize> = matches.get_one::<String>("max_length").map(|l| l.parse()).transpose()?;
+    let headcrop: Option<usize> = matches.get_one::<String>("headcrop").map(|h| h.parse()).transpose()?;
+    let crop: Option<usize> = matches.get_one::<String>("crop").map(|c| c.parse()).transpose()?;
     let trim_both_ends = !matches.get_flag("trim_3_only");
     let verbose = matches.get_flag("verbose");
 
@@ -89,6 +127,8 @@ fn main() -> Result<()> {
         println!("Min adapter length: {}", min_adapter_length);
         println!("Min overlap: {}", min_overlap);
         println!("Trim both ends: {}", trim_both_ends);
+        println!("Length limits: {}-{}", min_length, max_length.map_or("any".to_string(), |l| l.to_string()));
+        println!("Head crop: {}, crop: {}", headcrop.unwrap_or(0), crop.map_or("off".to_string(), |c| c.to_string()));
         println!();
     }
 
@@ -98,7 +138,13 @@ fn main() -> Result<()> {
     }
 
     // Create adapter trimmer
-    let trimmer = AdapterTrimmer::new(min_adapter_length, min_overlap, trim_both_ends);
+    let mut trimmer = AdapterTrimmer::new(min_adapter_length, min_overlap, trim_both_ends);
+    trimmer.length_rules = LengthRules {
+        headcrop: headcrop.unwrap_or(0),
+        crop,
+        min_length,
+        max_length,
+    };
 
     if verbose {
         println!("Processing FASTQ file...");
@@ -117,6 +163,18 @@ fn main() -> Result<()> {
                  stats.reads_with_adapters,
                  100.0 * stats.reads_with_adapters as f64 / stats.total_reads as f64);
         println!("Total bases trimmed: {}", stats.total_bases_trimmed);
+        println!("Length rules: {} head-cropped, {} cropped, {} too short, {} too long",
+                 stats.length_rules.reads_headcropped,
+                 stats.length_rules.reads_cropped,
+                 stats.length_rules.reads_too_short,
+                 stats.length_rules.reads_too_long);
+        println!("Read length: {}-{} before, {}-{} after (N50 {} → {})",
+                 stats.input_length_distribution.min_length,
+                 stats.input_length_distribution.max_length,
+                 stats.output_length_distribution.min_length,
+                 stats.output_length_distribution.max_length,
+                 stats.input_length_distribution.n50,
+                 stats.output_length_distribution.n50);
 
         if stats.reads_with_adapters > 0 {
             println!("Average bases trimmed per affected read: {:.1}", stats.average_trim_length);
@@ -136,6 +194,14 @@ fn main() -> Result<()> {
     let stats_json = serde_json::to_string_pretty(&stats)?;
     std::fs::write(&stats_path, stats_json)?;
 
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_path);
+        let metrics = stats.multiqc_metrics();
+        let written = write_general_stats("biometal-adapter-trim", "Illumina adapter trimming", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
     if verbose {
         println!("Statistics written to: {}", stats_path.display());
     }
@@ -4,6 +4,7 @@
 
 use anyhow::Result;
 use biometal_qc_tools::contamination::ContaminationScreener;
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
 use clap::{Arg, Command};
 use std::path::PathBuf;
 
@@ -50,11 +51,26 @@ fn main() -> Result<()> {
                 .help("Minimum read length")
                 .default_value("50"),
         )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .long("threads")
+                .value_name("N")
+                .help("Worker threads (output is identical for any thread count)")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_file = PathBuf::from(matches.get_one::<String>("input").unwrap());
     let output_file = PathBuf::from(matches.get_one::<String>("output").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let phix_threshold: f64 = matches
         .get_one::<String>("phix_threshold")
         .unwrap()
@@ -67,11 +83,13 @@ fn main() -> Result<()> {
         .get_one::<String>("min_length")
         .unwrap()
         .parse()?;
+    let threads: usize = matches.get_one::<String>("threads").unwrap().parse()?;
 
     println!("🔍 Biometal Contamination Screening Tool");
     println!("Input: {}", input_file.display());
     println!("Output: {}", output_file.display());
     println!("Min Length: {}", min_length);
+    println!("Threads: {}", threads);
 
     // Validate input file exists
     if !input_file.exists() {
@@ -79,7 +97,8 @@ fn main() -> Result<()> {
     }
 
     // Create contamination screener
-    let screener = ContaminationScreener::new(phix_threshold, vector_threshold, min_length);
+    let mut screener = ContaminationScreener::new(phix_threshold, vector_threshold, min_length);
+    screener.threads = threads;
 
     // Screen for contamination
     println!("🦠 Screening for contamination...");
@@ -92,6 +111,14 @@ fn main() -> Result<()> {
     let json_output = serde_json::to_string_pretty(&report)?;
     std::fs::write(&output_file, &json_output)?;
 
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_file);
+        let metrics = report.multiqc_metrics();
+        let written = write_general_stats("biometal-contamination-screen", "PhiX and vector contamination", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
     println!("✅ Contamination screening complete!");
     println!("📈 Sample: {}", report.sample_name);
     println!("🦠 PhiX contamination: {:.3}%", report.phix_percentage);
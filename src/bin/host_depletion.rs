@@ -6,6 +6,9 @@
 use anyhow::Result;
 use biometal::alignment::{StreamingMapper, StreamingMapperConfig, MappingResult};
 use biometal::{FastqStream, FastqWriter};
+use biometal_qc_tools::failed::FailedReadWriter;
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, Metric};
+use biometal_qc_tools::trimming::FailureReason;
 use clap::{Arg, Command};
 use serde::{Deserialize, Serialize};
 use serde_json;
@@ -53,6 +56,12 @@ fn main() -> Result<()> {
                 .help("Output host-depleted FASTQ file")
                 .required(true),
         )
+        .arg(
+            Arg::new("failed_out")
+                .long("failed-out")
+                .value_name("FASTQ")
+                .help("Write removed reads here, with reason=<tag> appended to the header"),
+        )
         .arg(
             Arg::new("stats")
                 .short('s')
@@ -83,13 +92,21 @@ fn main() -> Result<()> {
                 .help("Window overlap size in base pairs")
                 .default_value("200"),
         )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_path: PathBuf = matches.get_one::<String>("input").unwrap().into();
     let reference_path: PathBuf = matches.get_one::<String>("reference").unwrap().into();
     let output_path: PathBuf = matches.get_one::<String>("output").unwrap().into();
+    let failed_path: Option<PathBuf> = matches.get_one::<String>("failed_out").map(PathBuf::from);
     let stats_path: PathBuf = matches.get_one::<String>("stats").unwrap().into();
+    let multiqc_path: Option<PathBuf> = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let threshold: i32 = matches.get_one::<String>("threshold").unwrap().parse()?;
     let window_size: usize = matches.get_one::<String>("window_size").unwrap().parse()?;
     let overlap: usize = matches.get_one::<String>("overlap").unwrap().parse()?;
@@ -99,6 +116,9 @@ fn main() -> Result<()> {
     println!("Input reads: {}", input_path.display());
     println!("Host reference: {}", reference_path.display());
     println!("Output: {}", output_path.display());
+    if let Some(ref failed_path) = failed_path {
+        println!("Host reads: {}", failed_path.display());
+    }
     println!("Alignment threshold: {}", threshold);
     println!("Window size: {} bytes, Overlap: {} bp", window_size, overlap);
 
@@ -109,6 +129,7 @@ fn main() -> Result<()> {
         &input_path,
         &reference_path,
         &output_path,
+        failed_path.as_ref(),
         threshold,
         window_size,
         overlap,
@@ -124,6 +145,17 @@ fn main() -> Result<()> {
     let stats_json = serde_json::to_string_pretty(&final_stats)?;
     std::fs::write(&stats_path, stats_json)?;
 
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_path);
+        let metrics = vec![
+            Metric::percent("host_rate", "% Host", "Reads aligned to the host reference", final_stats.contamination_rate * 100.0),
+            Metric::count("clean_reads_kept", "Clean reads", "Reads kept after host depletion", final_stats.clean_reads_kept),
+        ];
+        let written = write_general_stats("biometal-host-depletion", "Host read removal", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
     println!("\n✅ Host Depletion Complete");
     println!("Total reads processed: {}", final_stats.total_reads);
     println!("Host matches found: {}", final_stats.host_matches_found);
@@ -139,6 +171,7 @@ fn process_host_depletion(
     input_path: &PathBuf,
     reference_path: &PathBuf,
     output_path: &PathBuf,
+    failed_path: Option<&PathBuf>,
     threshold: i32,
     window_size: usize,
     overlap: usize,
@@ -192,6 +225,7 @@ fn process_host_depletion(
 
     let fastq_stream = FastqStream::from_path(input_path)?;
     let mut writer = FastqWriter::create(output_path)?;
+    let mut failed_writer = failed_path.map(FailedReadWriter::create).transpose()?;
 
     let mut fastq_total_reads = 0;
     let mut host_matches_found = 0;
@@ -213,6 +247,9 @@ fn process_host_depletion(
         if is_host_contamination {
             host_matches_found += 1;
             // Skip host-contaminated reads
+            if let Some(ref mut failed_writer) = failed_writer {
+                failed_writer.write_record(&record, FailureReason::HostMatch)?;
+            }
         } else {
             // Keep non-host reads
             writer.write_record(&record)?;
@@ -224,6 +261,10 @@ fn process_host_depletion(
         }
     }
 
+    if let Some(failed_writer) = failed_writer {
+        failed_writer.finish()?;
+    }
+
     let contamination_rate = if fastq_total_reads > 0 {
         host_matches_found as f64 / fastq_total_reads as f64
     } else {
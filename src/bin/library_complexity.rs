@@ -0,0 +1,151 @@
+//! Biometal Library Complexity Tool
+//!
+//! Estimates library size and how many distinct reads deeper sequencing would
+//! yield, from exact-sequence duplicate counts or biometal-pcr-dedup clusters
+
+use anyhow::{Context, Result};
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, Metric};
+use biometal_qc_tools::parallel::{accumulate_fastq, DEFAULT_BATCH_SIZE};
+use biometal_qc_tools::saturation::{estimate_saturation, DuplicateHistogram, ExactDuplicateCounter};
+use clap::{Arg, ArgGroup, Command};
+use std::path::PathBuf;
+
+fn main() -> Result<()> {
+    let matches = Command::new("biometal-library-complexity")
+        .version("0.1.0")
+        .about("Library complexity and saturation curve estimation")
+        .author("Megan Johnson")
+        .arg(
+            Arg::new("input")
+                .short('i')
+                .long("input")
+                .value_name("FASTQ")
+                .help("Input FASTQ file (duplicates counted by exact sequence)"),
+        )
+        .arg(
+            Arg::new("pcr_dedup_stats")
+                .long("pcr-dedup-stats")
+                .value_name("JSON")
+                .help("Use cluster sizes from a biometal-pcr-dedup statistics file instead"),
+        )
+        .group(
+            ArgGroup::new("source")
+                .args(["input", "pcr_dedup_stats"])
+                .required(true),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("JSON")
+                .help("Output JSON file for the complexity estimate")
+                .default_value("library_complexity.json"),
+        )
+        .arg(
+            Arg::new("max_depth")
+                .long("max-depth")
+                .value_name("FOLD")
+                .help("Extrapolate the curve up to this multiple of the sequenced depth")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .long("threads")
+                .value_name("N")
+                .help("Worker threads (output is identical for any thread count)")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
+        .get_matches();
+
+    // Parse arguments
+    let input_file = matches.get_one::<String>("input").map(PathBuf::from);
+    let pcr_dedup_stats = matches.get_one::<String>("pcr_dedup_stats").map(PathBuf::from);
+    let output_file = PathBuf::from(matches.get_one::<String>("output").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
+    let max_depth: usize = matches.get_one::<String>("max_depth").unwrap().parse()?;
+    let threads: usize = matches.get_one::<String>("threads").unwrap().parse()?;
+
+    println!("📈 Biometal Library Complexity Tool");
+    println!("Output: {}", output_file.display());
+
+    let (source_file, histogram) = if let Some(input_file) = input_file {
+        println!("Input: {}", input_file.display());
+        println!("Threads: {}", threads);
+        if !input_file.exists() {
+            anyhow::bail!("Input file does not exist: {}", input_file.display());
+        }
+
+        println!("🔢 Counting exact-sequence duplicates...");
+        let counter = accumulate_fastq(&input_file, threads, DEFAULT_BATCH_SIZE, ExactDuplicateCounter::default)?;
+        (input_file, counter.finish())
+    } else {
+        let stats_file = pcr_dedup_stats.unwrap();
+        println!("PCR dedup stats: {}", stats_file.display());
+
+        let content = std::fs::read_to_string(&stats_file)?;
+        let stats: serde_json::Value = serde_json::from_str(&content)?;
+        let histogram: DuplicateHistogram = stats
+            .get("cluster_size_histogram")
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()?
+            .context("No cluster_size_histogram in PCR dedup stats (re-run biometal-pcr-dedup)")?;
+        (stats_file, histogram)
+    };
+
+    let estimate = estimate_saturation(&histogram, max_depth);
+
+    // Output results
+    let json_output = serde_json::to_string_pretty(&estimate)?;
+    std::fs::write(&output_file, &json_output)?;
+
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&source_file);
+        let metrics = vec![
+            Metric::count("distinct_reads", "Distinct", "Distinct molecules observed", estimate.distinct_reads),
+            Metric::value(
+                "estimated_library_size",
+                "Library size",
+                "Chao1 estimate of distinct molecules in the library",
+                estimate.estimated_library_size,
+            ),
+            Metric::percent(
+                "percent_of_library_observed",
+                "% Library seen",
+                "Distinct molecules observed as a share of the estimated library",
+                estimate.percent_of_library_observed,
+            ),
+        ];
+        let written = write_general_stats("biometal-library-complexity", "Library complexity", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
+    println!("✅ Complexity estimation complete!");
+    println!("📚 Reads: {}", estimate.total_reads);
+    println!("🧬 Distinct reads: {} ({} singletons, {} doubletons)",
+             estimate.distinct_reads, estimate.singletons, estimate.doubletons);
+    println!("📦 Estimated library size: {:.0} ({:.1}% observed)",
+             estimate.estimated_library_size, estimate.percent_of_library_observed);
+    println!("🆕 Share of next reads expected to be new: {:.2}%", estimate.probability_next_read_new * 100.0);
+    match estimate.reads_for_90_percent_of_library {
+        Some(reads) => println!("🎯 Reads to observe 90% of the library: {}", reads),
+        None => println!("🎯 90% of the library is out of reach at any depth"),
+    }
+    println!("📈 Expected distinct reads by depth:");
+    for point in estimate.curve.iter().filter(|p| p.relative_depth >= 1.0) {
+        println!("   {:>4.1}x  {:>12} reads  {:>12.0} distinct{}",
+                 point.relative_depth, point.reads, point.expected_distinct,
+                 if point.extrapolated { "" } else { " (observed)" });
+    }
+    println!("💾 Results saved to: {}", output_file.display());
+
+    Ok(())
+}
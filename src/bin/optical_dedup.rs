@@ -9,6 +9,8 @@ use biometal::operations::spatial::{
     IlluminaCoordinate
 };
 use biometal::{FastqStream, FastqWriter, FastqRecord};
+use biometal_qc_tools::encoding::{mean_phred, resolve_encoding, QualityEncoding};
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, Metric};
 use clap::{Arg, Command};
 use serde::{Deserialize, Serialize};
 use serde_json;
@@ -23,6 +25,7 @@ struct OpticalDedupStats {
     duplicate_groups: u64,
     average_group_size: f64,
     distance_threshold: f64,
+    phred_offset: u8,
     processing_time_seconds: f64,
 }
 
@@ -69,14 +72,29 @@ fn main() -> Result<()> {
                 .help("Keep highest quality read from each duplicate group")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("phred_offset")
+                .long("phred-offset")
+                .value_name("OFFSET")
+                .help("Quality score encoding: auto (detect from first reads), 33 or 64")
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_path: PathBuf = matches.get_one::<String>("input").unwrap().into();
     let output_path: PathBuf = matches.get_one::<String>("output").unwrap().into();
     let stats_path: PathBuf = matches.get_one::<String>("stats").unwrap().into();
+    let multiqc_path: Option<PathBuf> = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let threshold: f64 = matches.get_one::<String>("threshold").unwrap().parse()?;
     let keep_best_quality = matches.get_flag("keep_best");
+    let phred_offset = matches.get_one::<String>("phred_offset").unwrap();
 
     println!("🔬 Biometal Optical Duplicate Detection");
     println!("=====================================");
@@ -85,6 +103,10 @@ fn main() -> Result<()> {
     println!("Distance threshold: {} pixels", threshold);
     println!("Quality selection: {}", if keep_best_quality { "Best quality" } else { "First occurrence" });
 
+    // Resolve quality encoding used to pick the best read per group
+    let encoding = resolve_encoding(&input_path, phred_offset)?;
+    println!("Quality encoding: {}", encoding);
+
     let start_time = std::time::Instant::now();
 
     // Process optical duplicates
@@ -93,6 +115,7 @@ fn main() -> Result<()> {
         &output_path,
         threshold,
         keep_best_quality,
+        encoding,
     )?;
 
     let processing_time = start_time.elapsed().as_secs_f64();
@@ -105,6 +128,22 @@ fn main() -> Result<()> {
     let stats_json = serde_json::to_string_pretty(&final_stats)?;
     std::fs::write(&stats_path, stats_json)?;
 
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_path);
+        let metrics = vec![
+            Metric::percent(
+                "optical_duplicate_rate",
+                "% Optical dups",
+                "Reads removed as optical duplicates",
+                final_stats.optical_duplicates_found as f64 / final_stats.total_reads.max(1) as f64 * 100.0,
+            ),
+            Metric::count("unique_reads_kept", "Reads kept", "Reads kept after optical deduplication", final_stats.unique_reads_kept),
+        ];
+        let written = write_general_stats("biometal-optical-dedup", "Optical duplicate removal", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
     println!("\n✅ Optical Deduplication Complete");
     println!("Total reads processed: {}", final_stats.total_reads);
     println!("Optical duplicates found: {}", final_stats.optical_duplicates_found);
@@ -122,6 +161,7 @@ fn process_optical_duplicates(
     output_path: &PathBuf,
     threshold: f64,
     keep_best_quality: bool,
+    encoding: QualityEncoding,
 ) -> Result<OpticalDedupStats> {
     // Step 1: Read all records and extract coordinates
     let mut fastq_stream = FastqStream::from_path(input_path)?;
@@ -175,7 +215,7 @@ fn process_optical_duplicates(
         if group.len() > 1 {
             // Select representative (first or best quality)
             let representative = if keep_best_quality {
-                select_best_quality_read(group, &records_with_coords)?
+                select_best_quality_read(group, &records_with_coords, encoding)?
             } else {
                 group[0] // First occurrence
             };
@@ -219,19 +259,21 @@ fn process_optical_duplicates(
         duplicate_groups: duplicate_groups.len() as u64,
         average_group_size,
         distance_threshold: threshold,
+        phred_offset: encoding.offset(),
         processing_time_seconds: 0.0, // Will be set by caller
     })
 }
 
 fn select_best_quality_read(
     group: &[usize],
-    records_with_coords: &[(FastqRecord, IlluminaCoordinate)]
+    records_with_coords: &[(FastqRecord, IlluminaCoordinate)],
+    encoding: QualityEncoding,
 ) -> Result<usize> {
     let mut best_idx = group[0];
-    let mut best_quality = calculate_mean_quality(&records_with_coords[group[0]].0)?;
+    let mut best_quality = calculate_mean_quality(&records_with_coords[group[0]].0, encoding)?;
 
     for &idx in &group[1..] {
-        let quality = calculate_mean_quality(&records_with_coords[idx].0)?;
+        let quality = calculate_mean_quality(&records_with_coords[idx].0, encoding)?;
         if quality > best_quality {
             best_quality = quality;
             best_idx = idx;
@@ -241,14 +283,8 @@ fn select_best_quality_read(
     Ok(best_idx)
 }
 
-fn calculate_mean_quality(record: &FastqRecord) -> Result<f64> {
-    let qualities = &record.quality;
-    if qualities.is_empty() {
-        return Ok(0.0);
-    }
-
-    let sum: u32 = qualities.iter().map(|&q| (q - 33) as u32).sum();
-    Ok(sum as f64 / qualities.len() as f64)
+fn calculate_mean_quality(record: &FastqRecord, encoding: QualityEncoding) -> Result<f64> {
+    Ok(mean_phred(&record.quality, encoding))
 }
 
 // Helper function to create default coordinate for unparseable reads
@@ -6,6 +6,9 @@
 use anyhow::Result;
 use biometal::operations::kmer::{extract_minimizers_fast, Minimizer};
 use biometal::{FastqStream, FastqWriter, FastqRecord};
+use biometal_qc_tools::encoding::{mean_phred, resolve_encoding, QualityEncoding};
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, Metric};
+use biometal_qc_tools::saturation::{histogram_from_clusters, DuplicateHistogram};
 use clap::{Arg, Command};
 use serde::{Deserialize, Serialize};
 use serde_json;
@@ -22,7 +25,11 @@ struct PcrDedupStats {
     similarity_threshold: f64,
     kmer_size: usize,
     window_size: usize,
+    phred_offset: u8,
     processing_time_seconds: f64,
+    /// Molecules per cluster size, unclustered reads at size 1 (input for biometal-library-complexity)
+    #[serde(default)]
+    cluster_size_histogram: DuplicateHistogram,
 }
 
 fn main() -> Result<()> {
@@ -84,16 +91,31 @@ fn main() -> Result<()> {
                 .help("Keep highest quality read from each duplicate cluster")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("phred_offset")
+                .long("phred-offset")
+                .value_name("OFFSET")
+                .help("Quality score encoding: auto (detect from first reads), 33 or 64")
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_path: PathBuf = matches.get_one::<String>("input").unwrap().into();
     let output_path: PathBuf = matches.get_one::<String>("output").unwrap().into();
     let stats_path: PathBuf = matches.get_one::<String>("stats").unwrap().into();
+    let multiqc_path: Option<PathBuf> = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let threshold: f64 = matches.get_one::<String>("threshold").unwrap().parse()?;
     let kmer_size: usize = matches.get_one::<String>("kmer_size").unwrap().parse()?;
     let window_size: usize = matches.get_one::<String>("window_size").unwrap().parse()?;
     let keep_best_quality = matches.get_flag("keep_best");
+    let phred_offset = matches.get_one::<String>("phred_offset").unwrap();
 
     // Validate parameters
     if threshold < 0.0 || threshold > 1.0 {
@@ -108,6 +130,10 @@ fn main() -> Result<()> {
     println!("K-mer size: {}, Window size: {}", kmer_size, window_size);
     println!("Quality selection: {}", if keep_best_quality { "Best quality" } else { "First occurrence" });
 
+    // Resolve quality encoding used to pick the best read per cluster
+    let encoding = resolve_encoding(&input_path, phred_offset)?;
+    println!("Quality encoding: {}", encoding);
+
     let start_time = std::time::Instant::now();
 
     // Process PCR duplicates
@@ -118,6 +144,7 @@ fn main() -> Result<()> {
         kmer_size,
         window_size,
         keep_best_quality,
+        encoding,
     )?;
 
     let processing_time = start_time.elapsed().as_secs_f64();
@@ -130,6 +157,22 @@ fn main() -> Result<()> {
     let stats_json = serde_json::to_string_pretty(&final_stats)?;
     std::fs::write(&stats_path, stats_json)?;
 
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_path);
+        let metrics = vec![
+            Metric::percent(
+                "pcr_duplicate_rate",
+                "% PCR dups",
+                "Reads removed as PCR duplicates",
+                final_stats.pcr_duplicates_found as f64 / final_stats.total_reads.max(1) as f64 * 100.0,
+            ),
+            Metric::value("average_cluster_size", "Cluster size", "Mean reads per duplicate cluster", final_stats.average_cluster_size),
+        ];
+        let written = write_general_stats("biometal-pcr-dedup", "PCR duplicate removal", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
     println!("\n✅ PCR Deduplication Complete");
     println!("Total reads processed: {}", final_stats.total_reads);
     println!("PCR duplicates found: {}", final_stats.pcr_duplicates_found);
@@ -149,6 +192,7 @@ fn process_pcr_duplicates(
     kmer_size: usize,
     window_size: usize,
     keep_best_quality: bool,
+    encoding: QualityEncoding,
 ) -> Result<PcrDedupStats> {
     // Step 1: Read all records and extract minimizer signatures
     let fastq_stream = FastqStream::from_path(input_path)?;
@@ -199,7 +243,7 @@ fn process_pcr_duplicates(
         if cluster.len() > 1 {
             // Select representative (first or best quality)
             let representative = if keep_best_quality {
-                select_best_quality_read(cluster, &records_with_signatures)?
+                select_best_quality_read(cluster, &records_with_signatures, encoding)?
             } else {
                 cluster[0] // First occurrence
             };
@@ -243,7 +287,9 @@ fn process_pcr_duplicates(
         similarity_threshold: threshold,
         kmer_size,
         window_size,
+        phred_offset: encoding.offset(),
         processing_time_seconds: 0.0, // Will be set by caller
+        cluster_size_histogram: histogram_from_clusters(total_reads, &cluster_sizes),
     })
 }
 
@@ -322,13 +368,14 @@ fn calculate_jaccard_similarity(minimizers1: &[Minimizer], minimizers2: &[Minimi
 
 fn select_best_quality_read(
     cluster: &[usize],
-    records_with_signatures: &[(FastqRecord, Vec<Minimizer>)]
+    records_with_signatures: &[(FastqRecord, Vec<Minimizer>)],
+    encoding: QualityEncoding,
 ) -> Result<usize> {
     let mut best_idx = cluster[0];
-    let mut best_quality = calculate_mean_quality(&records_with_signatures[cluster[0]].0)?;
+    let mut best_quality = calculate_mean_quality(&records_with_signatures[cluster[0]].0, encoding)?;
 
     for &idx in &cluster[1..] {
-        let quality = calculate_mean_quality(&records_with_signatures[idx].0)?;
+        let quality = calculate_mean_quality(&records_with_signatures[idx].0, encoding)?;
         if quality > best_quality {
             best_quality = quality;
             best_idx = idx;
@@ -338,12 +385,6 @@ fn select_best_quality_read(
     Ok(best_idx)
 }
 
-fn calculate_mean_quality(record: &FastqRecord) -> Result<f64> {
-    let qualities = &record.quality;
-    if qualities.is_empty() {
-        return Ok(0.0);
-    }
-
-    let sum: u32 = qualities.iter().map(|&q| (q - 33) as u32).sum();
-    Ok(sum as f64 / qualities.len() as f64)
+fn calculate_mean_quality(record: &FastqRecord, encoding: QualityEncoding) -> Result<f64> {
+    Ok(mean_phred(&record.quality, encoding))
 }
\ No newline at end of file
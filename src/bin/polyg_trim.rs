@@ -1,11 +1,20 @@
 //! Biometal PolyG Trimmer Tool
 //!
-//! Remove polyG tails from NovaSeq FASTQ reads caused by 2-channel chemistry artifacts.
+//! Remove polyG tails from NovaSeq FASTQ reads caused by 2-channel chemistry artifacts,
+//! or any polyX tail (e.g. polyA in RNA libraries) on either end, tolerating
+//! miscalled bases like fastp `--trim_poly_x`. Optional HEADCROP/CROP and a
+//! maximum read length.
 
 use anyhow::Result;
 use biometal::io::{DataSource, FastqStream};
+use biometal_qc_tools::distributions::LengthDistributionAccumulator;
+use biometal_qc_tools::failed::FailedReadWriter;
+use biometal_qc_tools::polyx::{parse_polyx_bases, PolyXTrimmer, TailEnds};
+use biometal_qc_tools::trimming::{LengthRuleStats, LengthRules};
 use biometal_qc_tools::{get_file_info, PolyGStats};
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
 use clap::{Arg, Command};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::PathBuf;
@@ -13,7 +22,7 @@ use std::path::PathBuf;
 fn main() -> Result<()> {
     let matches = Command::new("biometal-polyg-trim")
         .version("0.1.0")
-        .about("Remove polyG tails from NovaSeq FASTQ reads")
+        .about("Remove polyG (or any polyX) tails from FASTQ reads")
         .author("Megan Johnson")
         .arg(
             Arg::new("input")
@@ -33,18 +42,79 @@ fn main() -> Result<()> {
         )
         .arg(
             Arg::new("min_polyg_length")
-                .long("min-polyg-length")
+                .long("min-polyx-length")
+                .visible_alias("min-polyg-length")
                 .value_name("LENGTH")
-                .help("Minimum consecutive Gs to trigger trimming")
+                .help("Minimum tail length to trigger trimming")
                 .default_value("10"),
         )
+        .arg(
+            Arg::new("poly_x")
+                .long("poly-x")
+                .value_name("BASES")
+                .help("Homopolymer bases to trim, e.g. G (NovaSeq), A (polyA) or ACGT")
+                .default_value("G"),
+        )
+        .arg(
+            Arg::new("poly_x_end")
+                .long("poly-x-end")
+                .value_name("END")
+                .help("Read end to trim tails from: 3, 5 or both")
+                .default_value("3"),
+        )
+        .arg(
+            Arg::new("mismatch_window")
+                .long("mismatch-window")
+                .value_name("BASES")
+                .help("Tail length over which --window-mismatches miscalls are allowed")
+                .default_value("8"),
+        )
+        .arg(
+            Arg::new("window_mismatches")
+                .long("window-mismatches")
+                .value_name("COUNT")
+                .help("Miscalled bases allowed per mismatch window; 0 trims exact runs only, 1 tolerates miscalls like fastp --trim_poly_x")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("max_mismatches")
+                .long("max-mismatches")
+                .value_name("COUNT")
+                .help("Most miscalled bases allowed in one tail")
+                .default_value("5"),
+        )
         .arg(
             Arg::new("min_read_length")
-                .long("min-read-length")
+                .long("min-length")
+                .visible_alias("min-read-length")
                 .value_name("LENGTH")
                 .help("Minimum read length after trimming")
                 .default_value("50"),
         )
+        .arg(
+            Arg::new("max_length")
+                .long("max-length")
+                .value_name("LENGTH")
+                .help("Discard reads longer than this after trimming"),
+        )
+        .arg(
+            Arg::new("headcrop")
+                .long("headcrop")
+                .value_name("BASES")
+                .help("Remove this many bases from the 5' end before any other trimming (Trimmomatic HEADCROP)"),
+        )
+        .arg(
+            Arg::new("crop")
+                .long("crop")
+                .value_name("BASES")
+                .help("Then keep at most this many bases from the 5' end (Trimmomatic CROP)"),
+        )
+        .arg(
+            Arg::new("failed_out")
+                .long("failed-out")
+                .value_name("FASTQ")
+                .help("Write discarded reads here, with reason=<tag> appended to the header"),
+        )
         .arg(
             Arg::new("stats")
                 .long("stats")
@@ -52,6 +122,12 @@ fn main() -> Result<()> {
                 .help("Output statistics JSON file")
                 .default_value("polyg_stats.json"),
         )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
@@ -61,24 +137,61 @@ fn main() -> Result<()> {
         .get_one::<String>("min_polyg_length")
         .unwrap()
         .parse()?;
+    let polyx = PolyXTrimmer {
+        bases: parse_polyx_bases(matches.get_one::<String>("poly_x").unwrap())?,
+        ends: matches.get_one::<String>("poly_x_end").unwrap().parse()?,
+        min_length: min_polyg_length,
+        mismatch_window: matches.get_one::<String>("mismatch_window").unwrap().parse()?,
+        mismatches_per_window: matches.get_one::<String>("window_mismatches").unwrap().parse()?,
+        max_mismatches: matches.get_one::<String>("max_mismatches").unwrap().parse()?,
+    };
+    if polyx.mismatch_window == 0 {
+        anyhow::bail!("--mismatch-window must be at least 1");
+    }
     let min_read_length: usize = matches
         .get_one::<String>("min_read_length")
         .unwrap()
         .parse()?;
+    let max_length: Option<usize> = matches.get_one::<String>("max_length").map(|l| l.parse()).transpose()?;
+    let headcrop: Option<usize> = matches.get_one::<String>("headcrop").map(|h| h.parse()).transpose()?;
+    let crop: Option<usize> = matches.get_one::<String>("crop").map(|c| c.parse()).transpose()?;
     let stats_file = PathBuf::from(matches.get_one::<String>("stats").unwrap());
+    let failed_file = matches.get_one::<String>("failed_out").map(PathBuf::from);
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
 
     println!("✂️  Biometal PolyG Trimmer");
     println!("Input: {}", input_file.display());
     println!("Output: {}", output_file.display());
-    println!("Min polyG length: {}", min_polyg_length);
+    println!(
+        "PolyX bases: {} ({} end{}), min length {}",
+        String::from_utf8_lossy(&polyx.bases),
+        match polyx.ends {
+            TailEnds::ThreePrime => "3'",
+            TailEnds::FivePrime => "5'",
+            TailEnds::Both => "both",
+        },
+        if polyx.ends == TailEnds::Both { "s" } else { "" },
+        min_polyg_length
+    );
+    if polyx.mismatches_per_window > 0 {
+        println!(
+            "Mismatches: {} per {} bases, at most {}",
+            polyx.mismatches_per_window, polyx.mismatch_window, polyx.max_mismatches
+        );
+    } else {
+        println!("Mismatches: none (exact runs)");
+    }
     println!("Min read length: {}", min_read_length);
 
     // Get sample name from input file
     let (sample_name, _) = get_file_info(&input_file)?;
 
     // Create trimmer and process
-    let trimmer = PolyGTrimmer::new(min_polyg_length, min_read_length);
-    let stats = trimmer.trim_reads(&input_file, &output_file, &sample_name)?;
+    let mut trimmer = PolyGTrimmer::new(polyx, min_read_length);
+    trimmer.length_rules.headcrop = headcrop.unwrap_or(0);
+    trimmer.length_rules.crop = crop;
+    trimmer.length_rules.max_length = max_length;
+    let stats = trimmer.trim_reads(&input_file, &output_file, failed_file.as_ref(), &sample_name)?;
 
     // Output statistics
     println!("📊 PolyG Trimming Results:");
@@ -89,42 +202,84 @@ fn main() -> Result<()> {
     println!("  Reads discarded: {} ({:.1}%)",
              stats.reads_discarded,
              (stats.reads_discarded as f64 / stats.total_reads as f64) * 100.0);
+    println!("    Too short: {}, too long: {}",
+             stats.length_rules.reads_too_short,
+             stats.length_rules.reads_too_long);
+    if trimmer.length_rules.crops() {
+        println!("  Reads head-cropped: {}, cropped: {}",
+                 stats.length_rules.reads_headcropped,
+                 stats.length_rules.reads_cropped);
+    }
     println!("  Total bases removed: {}", stats.total_bases_removed);
     println!("  Average trim length: {:.1} bases", stats.average_trim_length);
+    for (base, tails) in &stats.tails_by_base {
+        println!("  Poly{} tails: {}", base, tails);
+    }
+    if trimmer.polyx.ends == TailEnds::Both {
+        println!("  Tails by end: {} 5', {} 3'", stats.five_prime_tails, stats.three_prime_tails);
+    }
+    println!("  Read length: {}-{} before, {}-{} after (N50 {} → {})",
+             stats.input_length_distribution.min_length,
+             stats.input_length_distribution.max_length,
+             stats.output_length_distribution.min_length,
+             stats.output_length_distribution.max_length,
+             stats.input_length_distribution.n50,
+             stats.output_length_distribution.n50);
 
     // Export statistics to JSON
     let json_content = serde_json::to_string_pretty(&stats)?;
     std::fs::write(&stats_file, json_content)?;
+
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_file);
+        let metrics = stats.multiqc_metrics();
+        let written = write_general_stats("biometal-polyg-trim", "NovaSeq polyG tail trimming", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+    if let Some(ref failed_file) = failed_file {
+        println!("🗑️ Discarded reads saved to: {}", failed_file.display());
+    }
     println!("💾 Statistics saved to: {}", stats_file.display());
 
     Ok(())
 }
 
-/// PolyG trimmer implementation
+/// PolyG (polyX) trimmer implementation
 pub struct PolyGTrimmer {
-    pub min_polyg_length: usize,
-    pub min_read_length: usize,
+    pub polyx: PolyXTrimmer,
+    pub length_rules: LengthRules,
 }
 
 impl PolyGTrimmer {
-    pub fn new(min_polyg_length: usize, min_read_length: usize) -> Self {
+    pub fn new(polyx: PolyXTrimmer, min_read_length: usize) -> Self {
         Self {
-            min_polyg_length,
-            min_read_length,
+            polyx,
+            length_rules: LengthRules {
+                min_length: min_read_length,
+                ..LengthRules::default()
+            },
         }
     }
 
-    /// Trim polyG tails from FASTQ reads
+    /// Trim polyX tails from FASTQ reads; discarded reads go to `failed_path` untrimmed
     pub fn trim_reads(
         &self,
         input_path: &PathBuf,
         output_path: &PathBuf,
+        failed_path: Option<&PathBuf>,
         sample_name: &str,
     ) -> Result<PolyGStats> {
         let mut total_reads = 0u64;
         let mut reads_trimmed = 0u64;
         let mut reads_discarded = 0u64;
         let mut total_bases_removed = 0u64;
+        let mut input_lengths = LengthDistributionAccumulator::default();
+        let mut output_lengths = LengthDistributionAccumulator::default();
+        let mut length_rules = LengthRuleStats::default();
+        let mut tails_by_base = BTreeMap::new();
+        let mut five_prime_tails = 0u64;
+        let mut three_prime_tails = 0u64;
 
         // Open input stream
         let data_source = DataSource::from_path(input_path);
@@ -133,6 +288,7 @@ impl PolyGTrimmer {
         // Open output writer
         let output_file = File::create(output_path)?;
         let mut writer = BufWriter::new(output_file);
+        let mut failed_writer = failed_path.map(FailedReadWriter::create).transpose()?;
 
         // Process each read
         for record_result in fastq_stream {
@@ -141,28 +297,52 @@ impl PolyGTrimmer {
                 continue;
             }
             total_reads += 1;
+            input_lengths.add(record.sequence.len());
+            let crop = self.length_rules.crop_range(record.sequence.len(), &mut length_rules);
 
-            // Trim polyG tail
-            let (trimmed_sequence, trimmed_quality, trim_length) =
-                self.trim_polyg_tail(&record.sequence, &record.quality);
+            // Trim polyX tails
+            let sequence = &record.sequence[crop.clone()];
+            let quality = &record.quality[crop];
+            let polyx = self.polyx.trim(sequence);
+            let trim_length = sequence.len() - polyx.keep.len();
+            let trimmed_sequence = &sequence[polyx.keep.clone()];
+            let trimmed_quality = &quality[polyx.keep];
 
             if trim_length > 0 {
                 reads_trimmed += 1;
                 total_bases_removed += trim_length as u64;
             }
+            for (tail, end_count) in [
+                (polyx.five_prime, &mut five_prime_tails),
+                (polyx.three_prime, &mut three_prime_tails),
+            ] {
+                if let Some(tail) = tail {
+                    *end_count += 1;
+                    *tails_by_base.entry(tail.base).or_insert(0) += 1;
+                }
+            }
+
+            // Check if read meets the length limits
+            if let Some(reason) = self.length_rules.check(trimmed_sequence.len(), &mut length_rules) {
+                reads_discarded += 1;
+                if let Some(ref mut failed_writer) = failed_writer {
+                    failed_writer.write_record(&record, reason)?;
+                }
+            } else {
+                output_lengths.add(trimmed_sequence.len());
 
-            // Check if read meets minimum length requirement
-            if trimmed_sequence.len() >= self.min_read_length {
                 // Write trimmed FASTQ record
                 writeln!(writer, "@{}", record.id)?;
-                writeln!(writer, "{}", String::from_utf8_lossy(&trimmed_sequence))?;
+                writeln!(writer, "{}", String::from_utf8_lossy(trimmed_sequence))?;
                 writeln!(writer, "+")?;
-                writeln!(writer, "{}", String::from_utf8_lossy(&trimmed_quality))?;
-            } else {
-                reads_discarded += 1;
+                writeln!(writer, "{}", String::from_utf8_lossy(trimmed_quality))?;
             }
         }
 
+        if let Some(failed_writer) = failed_writer {
+            failed_writer.finish()?;
+        }
+
         let average_trim_length = if reads_trimmed > 0 {
             total_bases_removed as f64 / reads_trimmed as f64
         } else {
@@ -176,38 +356,12 @@ impl PolyGTrimmer {
             reads_discarded,
             total_bases_removed,
             average_trim_length,
+            tails_by_base,
+            five_prime_tails,
+            three_prime_tails,
+            length_rules,
+            input_length_distribution: input_lengths.finish(),
+            output_length_distribution: output_lengths.finish(),
         })
     }
-
-    /// Trim polyG tail from 3' end of read
-    fn trim_polyg_tail(&self, sequence: &[u8], quality: &[u8]) -> (Vec<u8>, Vec<u8>, usize) {
-        let seq_len = sequence.len();
-
-        // Scan from 3' end for consecutive Gs
-        let mut polyg_start = seq_len;
-        let mut consecutive_gs = 0;
-
-        for i in (0..seq_len).rev() {
-            if sequence[i] == b'G' || sequence[i] == b'g' {
-                consecutive_gs += 1;
-                if consecutive_gs >= self.min_polyg_length {
-                    polyg_start = i;
-                }
-            } else {
-                // Reset if we encounter a non-G base
-                consecutive_gs = 0;
-            }
-        }
-
-        if polyg_start < seq_len {
-            // Trim back to before the polyG tail
-            let trim_length = seq_len - polyg_start;
-            let trimmed_seq = sequence[0..polyg_start].to_vec();
-            let trimmed_qual = quality[0..polyg_start].to_vec();
-            (trimmed_seq, trimmed_qual, trim_length)
-        } else {
-            // No polyG tail found, return original
-            (sequence.to_vec(), quality.to_vec(), 0)
-        }
-    }
 }
\ No newline at end of file
@@ -5,6 +5,8 @@
 
 use anyhow::Result;
 use biometal_qc_tools::primers::PrimerRemover;
+use biometal_qc_tools::trimming::LengthRules;
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
 use clap::{Arg, Command};
 use serde_json;
 use std::path::PathBuf;
@@ -64,6 +66,31 @@ fn main() -> Result<()> {
                 .help("Cross-contamination threshold for flagging (%)")
                 .default_value("5.0"),
         )
+        .arg(
+            Arg::new("min_length")
+                .long("min-length")
+                .value_name("LENGTH")
+                .help("Discard reads shorter than this after trimming")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("max_length")
+                .long("max-length")
+                .value_name("LENGTH")
+                .help("Discard reads longer than this after trimming"),
+        )
+        .arg(
+            Arg::new("headcrop")
+                .long("headcrop")
+                .value_name("BASES")
+                .help("Remove this many bases from the 5' end before any other trimming (Trimmomatic HEADCROP)"),
+        )
+        .arg(
+            Arg::new("crop")
+                .long("crop")
+                .value_name("BASES")
+                .help("Then keep at most this many bases from the 5' end (Trimmomatic CROP)"),
+        )
         .arg(
             Arg::new("verbose")
                 .short('v')
@@ -71,15 +98,26 @@ fn main() -> Result<()> {
                 .help("Verbose output")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_path = PathBuf::from(matches.get_one::<String>("input").unwrap());
     let output_path = matches.get_one::<String>("output").map(PathBuf::from);
     let stats_path = PathBuf::from(matches.get_one::<String>("stats").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let min_match_length: usize = matches.get_one::<String>("min_match_length").unwrap().parse()?;
     let max_match_length: usize = matches.get_one::<String>("max_match_length").unwrap().parse()?;
     let contamination_threshold: f64 = matches.get_one::<String>("contamination_threshold").unwrap().parse()?;
+    let min_length: usize = matches.get_one::<String>("min_length").unwrap().parse()?;
+    let max_length: Option<usize> = matches.get_one::<String>("max_length").map(|l| l.parse()).transpose()?;
+    let headcrop: Option<usize> = matches.get_one::<String>("headcrop").map(|h| h.parse()).transpose()?;
+    let crop: Option<usize> = matches.get_one::<String>("crop").map(|c| c.parse()).transpose()?;
     let verbose = matches.get_flag("verbose");
 
     if verbose {
@@ -94,6 +132,8 @@ fn main() -> Result<()> {
         println!("Stats file: {}", stats_path.display());
         println!("K-mer range: {} to {} (BBDuk: mink={}, k={})", min_match_length, max_match_length, min_match_length, max_match_length);
         println!("Contamination threshold: {:.1}%", contamination_threshold);
+        println!("Length limits: {}-{}", min_length, max_length.map_or("any".to_string(), |l| l.to_string()));
+        println!("Head crop: {}, crop: {}", headcrop.unwrap_or(0), crop.map_or("off".to_string(), |c| c.to_string()));
         println!();
     }
 
@@ -114,7 +154,13 @@ fn main() -> Result<()> {
     }
 
     // Create primer remover
-    let remover = PrimerRemover::new(min_match_length, max_match_length, contamination_threshold);
+    let mut remover = PrimerRemover::new(min_match_length, max_match_length, contamination_threshold);
+    remover.length_rules = LengthRules {
+        headcrop: headcrop.unwrap_or(0),
+        crop,
+        min_length,
+        max_length,
+    };
 
     if verbose {
         println!("🔍 Processing FASTQ file...");
@@ -138,6 +184,18 @@ fn main() -> Result<()> {
                  stats.reads_with_rc_primers,
                  100.0 * stats.reads_with_rc_primers as f64 / stats.total_reads as f64);
         println!("Total bases trimmed: {}", stats.total_bases_trimmed);
+        println!("Length rules: {} head-cropped, {} cropped, {} too short, {} too long",
+                 stats.length_rules.reads_headcropped,
+                 stats.length_rules.reads_cropped,
+                 stats.length_rules.reads_too_short,
+                 stats.length_rules.reads_too_long);
+        println!("Read length: {}-{} before, {}-{} after (N50 {} → {})",
+                 stats.input_length_distribution.min_length,
+                 stats.input_length_distribution.max_length,
+                 stats.output_length_distribution.min_length,
+                 stats.output_length_distribution.max_length,
+                 stats.input_length_distribution.n50,
+                 stats.output_length_distribution.n50);
         println!("Cross-contamination level: {:.2}%", stats.contamination_level);
 
         if !stats.forward_primers_found.is_empty() {
@@ -171,6 +229,14 @@ fn main() -> Result<()> {
     let stats_json = serde_json::to_string_pretty(&stats)?;
     std::fs::write(&stats_path, stats_json)?;
 
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_path);
+        let metrics = stats.multiqc_metrics();
+        let written = write_general_stats("biometal-primer-remove", "PCR primer removal", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
     if verbose {
         println!("💾 Statistics saved to: {}", stats_path.display());
     }
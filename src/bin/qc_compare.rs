@@ -0,0 +1,151 @@
+//! Biometal QC Compare Tool
+//!
+//! Runs the quality analysis on a processing step's input and output FASTQ
+//! and reports read/base retention and quality deltas
+
+use anyhow::Result;
+use biometal_qc_tools::compare::compare_stats;
+use biometal_qc_tools::encoding::resolve_encoding;
+use biometal_qc_tools::html::write_comparison_report;
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
+use biometal_qc_tools::quality::QualityAnalyzer;
+use clap::{Arg, Command};
+use std::path::PathBuf;
+
+fn main() -> Result<()> {
+    let matches = Command::new("biometal-qc-compare")
+        .version("0.1.0")
+        .about("Before/after quality comparison for trimming and filtering steps")
+        .author("Megan Johnson")
+        .arg(
+            Arg::new("before")
+                .short('b')
+                .long("before")
+                .value_name("FASTQ")
+                .help("FASTQ given to the processing step")
+                .required(true),
+        )
+        .arg(
+            Arg::new("after")
+                .short('a')
+                .long("after")
+                .value_name("FASTQ")
+                .help("FASTQ written by the processing step")
+                .required(true),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("JSON")
+                .help("Output JSON file for the comparison")
+                .default_value("qc_comparison.json"),
+        )
+        .arg(
+            Arg::new("phred_offset")
+                .long("phred-offset")
+                .value_name("OFFSET")
+                .help("Quality score encoding: auto (detect from first reads), 33 or 64")
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("html")
+                .long("html")
+                .value_name("HTML")
+                .help("Also write a self-contained side-by-side HTML view"),
+        )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .long("threads")
+                .value_name("N")
+                .help("Worker threads (output is identical for any thread count)")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
+        .get_matches();
+
+    // Parse arguments
+    let before_file = PathBuf::from(matches.get_one::<String>("before").unwrap());
+    let after_file = PathBuf::from(matches.get_one::<String>("after").unwrap());
+    let output_file = PathBuf::from(matches.get_one::<String>("output").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
+    let html_file = matches.get_one::<String>("html").map(PathBuf::from);
+    let threads: usize = matches.get_one::<String>("threads").unwrap().parse()?;
+    let phred_offset = matches.get_one::<String>("phred_offset").unwrap();
+
+    println!("⚖️ Biometal QC Compare Tool");
+    println!("Before: {}", before_file.display());
+    println!("After: {}", after_file.display());
+    println!("Output: {}", output_file.display());
+    println!("Threads: {}", threads);
+
+    // Validate input files exist
+    for file in [&before_file, &after_file] {
+        if !file.exists() {
+            anyhow::bail!("Input file does not exist: {}", file.display());
+        }
+    }
+
+    // Both files are decoded with the input's encoding; processing does not change it
+    let encoding = resolve_encoding(&before_file, phred_offset)?;
+    println!("Quality encoding: {}", encoding);
+
+    // Count every non-empty read so retention is exact
+    let mut analyzer = QualityAnalyzer::new(20, 0);
+    analyzer.encoding = Some(encoding);
+    analyzer.threads = threads;
+
+    println!("📊 Analyzing input...");
+    let before = analyzer.analyze_fastq(&before_file)?;
+    println!("📊 Analyzing output...");
+    let after = analyzer.analyze_fastq(&after_file)?;
+
+    let comparison = compare_stats(&before, &after);
+
+    // Output results
+    let json_output = serde_json::to_string_pretty(&comparison)?;
+    std::fs::write(&output_file, &json_output)?;
+
+    if let Some(ref html_file) = html_file {
+        write_comparison_report(&comparison, html_file)?;
+    }
+
+    // MultiQC custom content (sample named after the processed file)
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&after_file);
+        let metrics = comparison.multiqc_metrics();
+        let written = write_general_stats("biometal-qc-compare", "Before/after processing comparison", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
+    println!("✅ Comparison complete!");
+    println!(
+        "📚 Reads: {} → {} ({:.2}% retained)",
+        comparison.reads_before, comparison.reads_after, comparison.read_retention
+    );
+    println!(
+        "🧬 Bases: {} → {} ({:.2}% retained)",
+        comparison.bases_before, comparison.bases_after, comparison.base_retention
+    );
+    for (label, metric) in [
+        ("⭐ Mean quality", &comparison.mean_quality),
+        ("🎯 Q30 bases (%)", &comparison.q30_bases),
+        ("🔬 GC content (%)", &comparison.gc_content),
+        ("📏 Mean length", &comparison.mean_length),
+        ("📏 N50", &comparison.n50),
+    ] {
+        println!("{}: {:.2} → {:.2} ({:+.2})", label, metric.before, metric.after, metric.delta);
+    }
+    println!("💾 Results saved to: {}", output_file.display());
+    if let Some(ref html_file) = html_file {
+        println!("📄 HTML report saved to: {}", html_file.display());
+    }
+
+    Ok(())
+}
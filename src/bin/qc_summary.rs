@@ -4,7 +4,9 @@
 
 use anyhow::Result;
 use biometal_qc_tools::contamination::ContaminationReport;
+use biometal_qc_tools::multiqc::{summary_section, MQC_SUFFIX};
 use biometal_qc_tools::reporting::{QcReporter, SampleQcReport};
+use biometal_qc_tools::verdicts::{overall_verdict, Verdict};
 use biometal_qc_tools::vlp::VlpReport;
 use biometal_qc_tools::QcStats;
 use clap::{Arg, Command};
@@ -47,11 +49,18 @@ fn main() -> Result<()> {
                 .help("Contamination threshold for pass/fail")
                 .default_value("0.1"),
         )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write an aggregated MultiQC custom content table (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_dir = PathBuf::from(matches.get_one::<String>("input_dir").unwrap());
     let output_file = PathBuf::from(matches.get_one::<String>("output").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let quality_threshold: f64 = matches
         .get_one::<String>("quality_threshold")
         .unwrap()
@@ -89,6 +98,10 @@ fn main() -> Result<()> {
 
     // Export to JSON
     reporter.export_json(&multi_sample_report, &output_file)?;
+    let multiqc_written = match multiqc_path {
+        Some(ref path) => Some(summary_section(&multi_sample_report).write(path)?),
+        None => None,
+    };
 
     println!("✅ QC summary complete!");
     println!("📊 Summary Statistics:");
@@ -98,7 +111,35 @@ fn main() -> Result<()> {
     println!("  Pass rate: {:.1}%", multi_sample_report.summary.pass_rate);
     println!("  Average quality: {:.2}", multi_sample_report.summary.average_quality);
     println!("  Average GC: {:.2}%", multi_sample_report.summary.average_gc_content);
+    println!("  Average reads remaining if deduplicated: {:.2}%",
+             multi_sample_report.summary.average_percent_remaining_if_deduplicated);
+    for sample in &multi_sample_report.samples {
+        println!("    {}: {:.2}% unique",
+                 sample.quality_stats.sample_name,
+                 sample.quality_stats.duplication.percent_remaining_if_deduplicated);
+    }
+    if !multi_sample_report.summary.module_verdicts.is_empty() {
+        println!("🚦 Module verdicts (PASS/WARN/FAIL samples):");
+        for counts in &multi_sample_report.summary.module_verdicts {
+            println!("    {}: {}/{}/{}", counts.module.title(), counts.pass, counts.warn, counts.fail);
+        }
+        for sample in &multi_sample_report.samples {
+            let verdicts = &sample.quality_stats.verdicts;
+            let flagged: Vec<String> = verdicts
+                .iter()
+                .filter(|v| v.verdict != Verdict::Pass)
+                .map(|v| format!("{} {}", v.module.title(), v.verdict))
+                .collect();
+            println!("    {}: {}{}",
+                     sample.quality_stats.sample_name,
+                     overall_verdict(verdicts),
+                     if flagged.is_empty() { String::new() } else { format!(" ({})", flagged.join(", ")) });
+        }
+    }
     println!("💾 Summary saved to: {}", output_file.display());
+    if let Some(ref written) = multiqc_written {
+        println!("📊 MultiQC table saved to: {}", written.display());
+    }
 
     Ok(())
 }
@@ -157,6 +198,7 @@ fn load_sample_reports(input_dir: &PathBuf) -> Result<Vec<SampleQcReport>> {
                     complexity_diversity: 0.0,
                     compositional_evenness: 0.0,
                     vlp_success_score: 0.0,
+                    gc_distribution: Default::default(),
                 }
             });
 
@@ -178,6 +220,11 @@ fn load_sample_reports(input_dir: &PathBuf) -> Result<Vec<SampleQcReport>> {
 /// Extract sample name from QC report filenames
 fn extract_sample_name(filename: &str) -> Option<String> {
     // Remove common QC report suffixes to get sample name
+    // MultiQC custom content written next to the stats is not a stats file
+    if filename.ends_with(MQC_SUFFIX) {
+        return None;
+    }
+
     let name = filename.strip_suffix(".json").unwrap_or(filename);
 
     if let Some(base) = name.strip_suffix("_quality_stats") {
@@ -1,12 +1,26 @@
 //! Biometal Quality Filter Tool
 //!
-//! Filter FASTQ reads based on mean quality scores using biometal primitives.
+//! Filter FASTQ reads based on mean quality scores using biometal primitives,
+//! with optional modified-Mott (cutadapt `-q`) and Trimmomatic-style LEADING,
+//! TRAILING and SLIDINGWINDOW trimming, HEADCROP/CROP and length limits,
+//! N-end trimming and N-content filters,
+//! expected-errors (maxEE) filters, low-complexity filters and DUST masking.
 
 use anyhow::Result;
 use biometal::io::{DataSource, FastqStream};
-use biometal::operations::mean_quality;
+use biometal::operations::complexity_score;
+use biometal_qc_tools::complexity::{trinucleotide_entropy, DustMasker, MaskMode};
+use biometal_qc_tools::distributions::{ExpectedErrorsAccumulator, LengthDistributionAccumulator};
+use biometal_qc_tools::failed::FailedReadWriter;
+use biometal_qc_tools::encoding::{expected_errors, mean_phred, resolve_encoding, QualityEncoding};
+use biometal_qc_tools::trimming::{
+    count_ambiguous, FailureReason, LengthRuleStats, LengthRules, MottCutoffs, QualityTrimmer, SlidingWindow,
+    TrimStep,
+};
 use biometal_qc_tools::{get_file_info, QualityFilterStats};
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
 use clap::{Arg, Command};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::PathBuf;
@@ -39,6 +53,124 @@ fn main() -> Result<()> {
                 .help("Minimum mean quality score threshold")
                 .default_value("20.0"),
         )
+        .arg(
+            Arg::new("mott")
+                .long("mott-quality")
+                .value_name("[5'CUTOFF,]3'CUTOFF")
+                .help("Modified-Mott quality trimming, same algorithm and syntax as cutadapt -q (e.g. 20 or 15,20)"),
+        )
+        .arg(
+            Arg::new("leading")
+                .long("leading")
+                .value_name("QUALITY")
+                .help("Trim 5' bases below this quality (Trimmomatic LEADING)"),
+        )
+        .arg(
+            Arg::new("trailing")
+                .long("trailing")
+                .value_name("QUALITY")
+                .help("Trim 3' bases below this quality (Trimmomatic TRAILING)"),
+        )
+        .arg(
+            Arg::new("sliding_window")
+                .long("sliding-window")
+                .value_name("SIZE:QUALITY")
+                .help("Cut where the mean quality of a window drops below QUALITY (Trimmomatic SLIDINGWINDOW, e.g. 4:15)"),
+        )
+        .arg(
+            Arg::new("trim_ns")
+                .long("trim-ns")
+                .action(clap::ArgAction::SetTrue)
+                .help("Trim Ns (and other ambiguity codes) from both ends after quality trimming"),
+        )
+        .arg(
+            Arg::new("max_n_count")
+                .long("max-n-count")
+                .value_name("COUNT")
+                .help("Discard reads with more than COUNT Ns after trimming"),
+        )
+        .arg(
+            Arg::new("max_n_fraction")
+                .long("max-n-fraction")
+                .value_name("FRACTION")
+                .help("Discard reads whose N fraction after trimming exceeds FRACTION (0-1)"),
+        )
+        .arg(
+            Arg::new("max_ee")
+                .long("max-ee")
+                .value_name("ERRORS")
+                .help("Discard reads whose expected errors after trimming exceed ERRORS (vsearch --fastq_maxee)"),
+        )
+        .arg(
+            Arg::new("max_ee_rate")
+                .long("max-ee-rate")
+                .value_name("RATE")
+                .help("Discard reads whose expected errors per base after trimming exceed RATE (vsearch --fastq_maxee_rate)"),
+        )
+        .arg(
+            Arg::new("min_complexity")
+                .long("min-complexity")
+                .value_name("SCORE")
+                .help("Discard reads whose biometal complexity score is below SCORE"),
+        )
+        .arg(
+            Arg::new("min_entropy")
+                .long("min-entropy")
+                .value_name("ENTROPY")
+                .help("Discard reads whose trinucleotide entropy is below ENTROPY (0-1, prinseq-lite style)"),
+        )
+        .arg(
+            Arg::new("dust")
+                .long("dust")
+                .value_name("MODE")
+                .help("Mask low-complexity stretches of written reads with DUST: n (replace with N) or soft (lowercase)"),
+        )
+        .arg(
+            Arg::new("dust_threshold")
+                .long("dust-threshold")
+                .value_name("LEVEL")
+                .help("DUST level: mask stretches whose mean triplet repeat score exceeds LEVEL/10 (sdust -t)")
+                .default_value("20"),
+        )
+        .arg(
+            Arg::new("dust_window")
+                .long("dust-window")
+                .value_name("BASES")
+                .help("DUST window size")
+                .default_value("64"),
+        )
+        .arg(
+            Arg::new("min_length")
+                .short('l')
+                .long("min-length")
+                .value_name("LENGTH")
+                .help("Discard reads shorter than this after trimming")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("max_length")
+                .long("max-length")
+                .value_name("LENGTH")
+                .help("Discard reads longer than this after trimming"),
+        )
+        .arg(
+            Arg::new("headcrop")
+                .long("headcrop")
+                .value_name("BASES")
+                .help("Remove this many bases from the 5' end before any other trimming (Trimmomatic HEADCROP)"),
+        )
+        .arg(
+            Arg::new("crop")
+                .long("crop")
+                .value_name("BASES")
+                .help("Then keep at most this many bases from the 5' end (Trimmomatic CROP)"),
+        )
+        .arg(
+            Arg::new("failed_out")
+                .long("failed-out")
+                .value_name("FASTQ")
+                .help("Write discarded reads here, with reason=<tag> appended to the header"),
+        )
         .arg(
             Arg::new("stats")
                 .long("stats")
@@ -46,6 +178,19 @@ fn main() -> Result<()> {
                 .help("Output statistics JSON file")
                 .default_value("quality_filter_stats.json"),
         )
+        .arg(
+            Arg::new("phred_offset")
+                .long("phred-offset")
+                .value_name("OFFSET")
+                .help("Quality score encoding: auto (detect from first reads), 33 or 64")
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
@@ -56,18 +201,123 @@ fn main() -> Result<()> {
         .unwrap()
         .parse()?;
     let stats_file = PathBuf::from(matches.get_one::<String>("stats").unwrap());
+    let failed_file = matches.get_one::<String>("failed_out").map(PathBuf::from);
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
+    let phred_offset = matches.get_one::<String>("phred_offset").unwrap();
+    let mott: Option<MottCutoffs> = matches.get_one::<String>("mott").map(|q| q.parse()).transpose()?;
+    let leading: Option<u8> = matches.get_one::<String>("leading").map(|q| q.parse()).transpose()?;
+    let trailing: Option<u8> = matches.get_one::<String>("trailing").map(|q| q.parse()).transpose()?;
+    let sliding_window: Option<SlidingWindow> = matches
+        .get_one::<String>("sliding_window")
+        .map(|w| w.parse())
+        .transpose()?;
+    let min_length: usize = matches
+        .get_one::<String>("min_length")
+        .unwrap()
+        .parse()?;
+    let max_length: Option<usize> = matches.get_one::<String>("max_length").map(|l| l.parse()).transpose()?;
+    let headcrop: Option<usize> = matches.get_one::<String>("headcrop").map(|h| h.parse()).transpose()?;
+    let crop: Option<usize> = matches.get_one::<String>("crop").map(|c| c.parse()).transpose()?;
+    let trim_ns = matches.get_flag("trim_ns");
+    let max_n_count: Option<usize> = matches.get_one::<String>("max_n_count").map(|n| n.parse()).transpose()?;
+    let max_n_fraction: Option<f64> = matches
+        .get_one::<String>("max_n_fraction")
+        .map(|f| f.parse())
+        .transpose()?;
+    if max_n_fraction.is_some_and(|fraction| !(0.0..=1.0).contains(&fraction)) {
+        anyhow::bail!("--max-n-fraction must be between 0 and 1");
+    }
+    let max_ee: Option<f64> = matches.get_one::<String>("max_ee").map(|e| e.parse()).transpose()?;
+    let max_ee_rate: Option<f64> = matches.get_one::<String>("max_ee_rate").map(|r| r.parse()).transpose()?;
+    if max_ee.is_some_and(|errors| errors < 0.0) || max_ee_rate.is_some_and(|rate| rate < 0.0) {
+        anyhow::bail!("--max-ee and --max-ee-rate must not be negative");
+    }
+    let min_complexity: Option<f64> = matches
+        .get_one::<String>("min_complexity")
+        .map(|c| c.parse())
+        .transpose()?;
+    let min_entropy: Option<f64> = matches.get_one::<String>("min_entropy").map(|e| e.parse()).transpose()?;
+    if min_entropy.is_some_and(|entropy| !(0.0..=1.0).contains(&entropy)) {
+        anyhow::bail!("--min-entropy must be between 0 and 1");
+    }
+    let dust_mode: Option<MaskMode> = matches.get_one::<String>("dust").map(|m| m.parse()).transpose()?;
+    let dust_threshold: f64 = matches
+        .get_one::<String>("dust_threshold")
+        .unwrap()
+        .parse()?;
+    let dust_window: usize = matches
+        .get_one::<String>("dust_window")
+        .unwrap()
+        .parse()?;
+    if dust_window < 4 {
+        anyhow::bail!("--dust-window must be at least 4 bases");
+    }
 
     println!("🎯 Biometal Quality Filter");
     println!("Input: {}", input_file.display());
     println!("Output: {}", output_file.display());
     println!("Min quality threshold: {:.1}", min_quality);
+    println!("Min length after trimming: {}", min_length);
+    if let Some(max_length) = max_length {
+        println!("Max length after trimming: {}", max_length);
+    }
+
+    // Resolve quality encoding (detected from the first reads unless overridden)
+    let encoding = resolve_encoding(&input_file, phred_offset)?;
+    println!("Quality encoding: {}", encoding);
 
     // Get sample name from input file
     let (sample_name, _) = get_file_info(&input_file)?;
 
     // Create filter and process
-    let filter = QualityFilter::new(min_quality);
-    let stats = filter.filter_reads(&input_file, &output_file, &sample_name)?;
+    let mut filter = QualityFilter::new(min_quality, encoding);
+    filter.trimmer.mott = mott;
+    filter.trimmer.leading = leading;
+    filter.trimmer.trailing = trailing;
+    filter.trimmer.sliding_window = sliding_window;
+    filter.trimmer.trim_ns = trim_ns;
+    filter.length_rules = LengthRules {
+        headcrop: headcrop.unwrap_or(0),
+        crop,
+        min_length: min_length.max(1),
+        max_length,
+    };
+    filter.max_n_count = max_n_count;
+    filter.max_n_fraction = max_n_fraction;
+    filter.max_ee = max_ee;
+    filter.max_ee_rate = max_ee_rate;
+    filter.min_complexity = min_complexity;
+    filter.min_entropy = min_entropy;
+    filter.dust = dust_mode.map(|mode| DustMasker {
+        window: dust_window,
+        ..DustMasker::new(dust_threshold, mode)
+    });
+    if filter.length_rules.crops() {
+        println!(
+            "Cropping: head crop {}, crop to {}",
+            filter.length_rules.headcrop,
+            crop.map_or("off".to_string(), |c| format!("{} bases", c))
+        );
+    }
+    if filter.trimmer.is_enabled() {
+        println!(
+            "Trimming: Mott {}, leading {}, trailing {}, sliding window {}, N ends {}",
+            mott.map_or("off".to_string(), |m| format!("{},{}", m.five_prime, m.three_prime)),
+            leading.map_or("off".to_string(), |q| format!("Q{}", q)),
+            trailing.map_or("off".to_string(), |q| format!("Q{}", q)),
+            sliding_window.map_or("off".to_string(), |w| format!("{}:{}", w.window_size, w.required_quality)),
+            if trim_ns { "on" } else { "off" }
+        );
+    }
+    if let Some(ref dust) = filter.dust {
+        println!(
+            "DUST masking: {} (window {}, level {})",
+            dust.mode.label(),
+            dust.window,
+            dust.threshold
+        );
+    }
+    let stats = filter.filter_reads(&input_file, &output_file, failed_file.as_ref(), &sample_name)?;
 
     // Output statistics
     println!("📊 Quality Filtering Results:");
@@ -78,11 +328,54 @@ fn main() -> Result<()> {
     println!("  Reads failed: {} ({:.1}%)",
              stats.reads_failed,
              100.0 - stats.pass_rate);
+    for (reason, count) in &stats.reads_failed_by_reason {
+        println!("    {}: {}", reason.label(), count);
+    }
     println!("  Quality threshold: {:.1}", stats.quality_threshold);
+    println!("  Reads with Ns: {} ({} N bases in, {} out)",
+             stats.reads_with_n,
+             stats.input_n_bases,
+             stats.output_n_bases);
+    println!("  Expected errors per read: mean {:.2} in, {:.2} out",
+             stats.input_expected_errors.mean_expected_errors,
+             stats.output_expected_errors.mean_expected_errors);
+    if filter.dust.is_some() {
+        println!("  Reads masked: {} ({} bases)", stats.reads_masked, stats.bases_masked);
+    }
+    if filter.length_rules.crops() || max_length.is_some() {
+        println!("  Length rules: {} head-cropped, {} cropped, {} too short, {} too long",
+                 stats.length_rules.reads_headcropped,
+                 stats.length_rules.reads_cropped,
+                 stats.length_rules.reads_too_short,
+                 stats.length_rules.reads_too_long);
+    }
+    if filter.trimmer.is_enabled() || filter.length_rules.crops() {
+        println!("  Reads trimmed: {}", stats.reads_trimmed);
+        for (step, bases) in &stats.bases_trimmed {
+            println!("    {} bases removed: {}", step.label(), bases);
+        }
+        println!(
+            "  Read length after trimming: {}-{} (mean {:.1})",
+            stats.output_length_distribution.min_length,
+            stats.output_length_distribution.max_length,
+            stats.output_length_distribution.mean_length
+        );
+    }
 
     // Export statistics to JSON
     let json_content = serde_json::to_string_pretty(&stats)?;
     std::fs::write(&stats_file, json_content)?;
+
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_file);
+        let metrics = stats.multiqc_metrics();
+        let written = write_general_stats("biometal-quality-filter", "Mean quality read filtering", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+    if let Some(ref failed_file) = failed_file {
+        println!("🗑️ Failed reads saved to: {}", failed_file.display());
+    }
     println!("💾 Statistics saved to: {}", stats_file.display());
 
     Ok(())
@@ -91,22 +384,63 @@ fn main() -> Result<()> {
 /// Quality filter implementation
 pub struct QualityFilter {
     pub min_quality: f64,
+    pub encoding: QualityEncoding,
+    pub trimmer: QualityTrimmer,
+    pub length_rules: LengthRules,
+    pub max_n_count: Option<usize>,
+    pub max_n_fraction: Option<f64>,
+    pub max_ee: Option<f64>,
+    pub max_ee_rate: Option<f64>,
+    pub min_complexity: Option<f64>,
+    pub min_entropy: Option<f64>,
+    pub dust: Option<DustMasker>,
 }
 
 impl QualityFilter {
-    pub fn new(min_quality: f64) -> Self {
-        Self { min_quality }
+    pub fn new(min_quality: f64, encoding: QualityEncoding) -> Self {
+        Self {
+            min_quality,
+            encoding,
+            trimmer: QualityTrimmer::new(encoding),
+            length_rules: LengthRules {
+                min_length: 1,
+                ..LengthRules::default()
+            },
+            max_n_count: None,
+            max_n_fraction: None,
+            max_ee: None,
+            max_ee_rate: None,
+            min_complexity: None,
+            min_entropy: None,
+            dust: None,
+        }
     }
 
-    /// Filter reads based on mean quality score
+    /// Trim reads, then filter them on length and mean quality score
+    ///
+    /// Failed reads go to `failed_path` untrimmed, tagged with the first check they failed.
     pub fn filter_reads(
         &self,
         input_path: &PathBuf,
         output_path: &PathBuf,
+        failed_path: Option<&PathBuf>,
         sample_name: &str,
     ) -> Result<QualityFilterStats> {
         let mut total_reads = 0u64;
         let mut reads_passed = 0u64;
+        let mut reads_trimmed = 0u64;
+        let mut bases_trimmed = BTreeMap::new();
+        let mut reads_failed_by_reason = BTreeMap::new();
+        let mut length_rules = LengthRuleStats::default();
+        let mut input_lengths = LengthDistributionAccumulator::default();
+        let mut output_lengths = LengthDistributionAccumulator::default();
+        let mut input_expected_errors = ExpectedErrorsAccumulator::default();
+        let mut output_expected_errors = ExpectedErrorsAccumulator::default();
+        let mut reads_with_n = 0u64;
+        let mut input_n_bases = 0u64;
+        let mut output_n_bases = 0u64;
+        let mut reads_masked = 0u64;
+        let mut bases_masked = 0u64;
 
         // Open input stream
         let data_source = DataSource::from_path(input_path);
@@ -115,6 +449,7 @@ impl QualityFilter {
         // Open output writer
         let output_file = File::create(output_path)?;
         let mut writer = BufWriter::new(output_file);
+        let mut failed_writer = failed_path.map(FailedReadWriter::create).transpose()?;
 
         // Process each read
         for record_result in fastq_stream {
@@ -124,19 +459,97 @@ impl QualityFilter {
             }
 
             total_reads += 1;
+            input_lengths.add(record.sequence.len());
+            input_expected_errors.add(expected_errors(&record.quality, self.encoding));
+            let read_n_bases = count_ambiguous(&record.sequence);
+            if read_n_bases > 0 {
+                reads_with_n += 1;
+                input_n_bases += read_n_bases as u64;
+            }
 
-            // Calculate mean quality using biometal primitive
-            let read_mean_quality = mean_quality(&record.quality);
-
-            // Check if read passes quality threshold
-            if read_mean_quality >= self.min_quality {
-                reads_passed += 1;
-                // Write FASTQ record
-                writeln!(writer, "@{}", record.id)?;
-                writeln!(writer, "{}", String::from_utf8_lossy(&record.sequence))?;
-                writeln!(writer, "+")?;
-                writeln!(writer, "{}", String::from_utf8_lossy(&record.quality))?;
+            // Fixed crops, quality and N trimming, then checks on what is left
+            let crop = self.length_rules.crop_range(record.sequence.len(), &mut length_rules);
+            let trim = self.trimmer.trim(&record.sequence[crop.clone()], &record.quality[crop.clone()]);
+            let mut removed = trim.removed;
+            for (step, bases) in [(TrimStep::HeadCrop, crop.start), (TrimStep::Crop, record.sequence.len() - crop.end)] {
+                if bases > 0 {
+                    removed.push((step, bases));
+                }
+            }
+            if !removed.is_empty() {
+                reads_trimmed += 1;
             }
+            for (step, bases) in removed {
+                *bases_trimmed.entry(step).or_insert(0) += bases as u64;
+            }
+            let keep = crop.start + trim.keep.start..crop.start + trim.keep.end;
+            let sequence = &record.sequence[keep.clone()];
+            let quality = &record.quality[keep];
+
+            // Calculate mean quality with the resolved Phred offset
+            let read_mean_quality = mean_phred(quality, self.encoding);
+
+            let n_bases = count_ambiguous(sequence);
+            let too_many_ns = self.max_n_count.is_some_and(|max| n_bases > max)
+                || self
+                    .max_n_fraction
+                    .is_some_and(|max| n_bases as f64 > max * sequence.len() as f64);
+
+            let read_expected_errors = expected_errors(quality, self.encoding);
+            let too_many_errors = self.max_ee.is_some_and(|max| read_expected_errors > max)
+                || self
+                    .max_ee_rate
+                    .is_some_and(|max| read_expected_errors > max * sequence.len() as f64);
+
+            let low_complexity = self.min_complexity.is_some_and(|min| complexity_score(sequence) < min)
+                || self.min_entropy.is_some_and(|min| trinucleotide_entropy(sequence) < min);
+
+            let failure = if let Some(reason) = self.length_rules.check(sequence.len(), &mut length_rules) {
+                Some(reason)
+            } else if too_many_ns {
+                Some(FailureReason::TooManyNs)
+            } else if low_complexity {
+                Some(FailureReason::LowComplexity)
+            } else if too_many_errors {
+                Some(FailureReason::TooManyExpectedErrors)
+            } else if read_mean_quality < self.min_quality {
+                Some(FailureReason::LowMeanQuality)
+            } else {
+                None
+            };
+
+            if let Some(reason) = failure {
+                *reads_failed_by_reason.entry(reason).or_insert(0) += 1;
+                if let Some(ref mut failed_writer) = failed_writer {
+                    failed_writer.write_record(&record, reason)?;
+                }
+                continue;
+            }
+
+            reads_passed += 1;
+            output_n_bases += n_bases as u64;
+            output_lengths.add(sequence.len());
+            output_expected_errors.add(read_expected_errors);
+
+            // Mask after the N checks so they only see the sequencer's Ns
+            let mut sequence = sequence.to_vec();
+            if let Some(ref dust) = self.dust {
+                let masked = dust.mask(&mut sequence);
+                if masked > 0 {
+                    reads_masked += 1;
+                    bases_masked += masked as u64;
+                }
+            }
+
+            // Write FASTQ record
+            writeln!(writer, "@{}", record.id)?;
+            writeln!(writer, "{}", String::from_utf8_lossy(&sequence))?;
+            writeln!(writer, "+")?;
+            writeln!(writer, "{}", String::from_utf8_lossy(quality))?;
+        }
+
+        if let Some(failed_writer) = failed_writer {
+            failed_writer.finish()?;
         }
 
         let reads_failed = total_reads - reads_passed;
@@ -153,6 +566,28 @@ impl QualityFilter {
             reads_failed,
             pass_rate,
             quality_threshold: self.min_quality,
+            phred_offset: self.encoding.offset(),
+            min_length: self.length_rules.min_length,
+            length_rules,
+            reads_trimmed,
+            bases_trimmed,
+            reads_failed_by_reason,
+            reads_with_n,
+            input_n_bases,
+            output_n_bases,
+            max_n_count: self.max_n_count,
+            max_n_fraction: self.max_n_fraction,
+            max_ee: self.max_ee,
+            max_ee_rate: self.max_ee_rate,
+            min_complexity: self.min_complexity,
+            min_entropy: self.min_entropy,
+            dust_threshold: self.dust.as_ref().map(|dust| dust.threshold),
+            reads_masked,
+            bases_masked,
+            input_length_distribution: input_lengths.finish(),
+            output_length_distribution: output_lengths.finish(),
+            input_expected_errors: input_expected_errors.finish(),
+            output_expected_errors: output_expected_errors.finish(),
         })
     }
 }
\ No newline at end of file
@@ -4,7 +4,11 @@
 //! Uses proven biometal primitives: base_counting, gc_content, quality_filter, complexity
 
 use anyhow::Result;
+use biometal_qc_tools::encoding::resolve_encoding;
+use biometal_qc_tools::html::write_quality_report;
 use biometal_qc_tools::quality::QualityAnalyzer;
+use biometal_qc_tools::verdicts::{overall_verdict, QcLimits};
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
 use clap::{Arg, Command};
 use std::path::PathBuf;
 
@@ -45,11 +49,45 @@ fn main() -> Result<()> {
                 .help("Minimum read length")
                 .default_value("50"),
         )
+        .arg(
+            Arg::new("phred_offset")
+                .long("phred-offset")
+                .value_name("OFFSET")
+                .help("Quality score encoding: auto (detect from first reads), 33 or 64")
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("limits")
+                .long("limits")
+                .value_name("FILE")
+                .help("FastQC-style limits file for PASS/WARN/FAIL verdicts (default: FastQC thresholds)"),
+        )
+        .arg(
+            Arg::new("html")
+                .long("html")
+                .value_name("HTML")
+                .help("Also write a self-contained HTML report (no network assets)"),
+        )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .long("threads")
+                .value_name("N")
+                .help("Worker threads (output is identical for any thread count)")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_file = PathBuf::from(matches.get_one::<String>("input").unwrap());
     let output_file = PathBuf::from(matches.get_one::<String>("output").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let min_quality: u8 = matches
         .get_one::<String>("min_quality")
         .unwrap()
@@ -58,19 +96,34 @@ fn main() -> Result<()> {
         .get_one::<String>("min_length")
         .unwrap()
         .parse()?;
+    let threads: usize = matches.get_one::<String>("threads").unwrap().parse()?;
+    let phred_offset = matches.get_one::<String>("phred_offset").unwrap();
+    let html_file = matches.get_one::<String>("html").map(PathBuf::from);
+    let limits = match matches.get_one::<String>("limits") {
+        Some(path) => QcLimits::from_file(path)?,
+        None => QcLimits::default(),
+    };
 
     println!("🧬 Biometal Quality Stats Tool");
     println!("Input: {}", input_file.display());
     println!("Output: {}", output_file.display());
     println!("Min Quality: {}, Min Length: {}", min_quality, min_length);
+    println!("Threads: {}", threads);
 
     // Validate input file exists
     if !input_file.exists() {
         anyhow::bail!("Input file does not exist: {}", input_file.display());
     }
 
+    // Resolve quality encoding (detected from the first reads unless overridden)
+    let encoding = resolve_encoding(&input_file, phred_offset)?;
+    println!("Quality encoding: {}", encoding);
+
     // Create quality analyzer
-    let analyzer = QualityAnalyzer::new(min_quality, min_length);
+    let mut analyzer = QualityAnalyzer::new(min_quality, min_length);
+    analyzer.encoding = Some(encoding);
+    analyzer.threads = threads;
+    analyzer.limits = limits;
 
     // Analyze the FASTQ file
     println!("📊 Analyzing quality statistics...");
@@ -80,15 +133,108 @@ fn main() -> Result<()> {
     let json_output = serde_json::to_string_pretty(&stats)?;
     std::fs::write(&output_file, &json_output)?;
 
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_file);
+        let metrics = stats.multiqc_metrics();
+        let written = write_general_stats("biometal-quality-stats", "Read quality statistics", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+    if let Some(ref html_file) = html_file {
+        write_quality_report(&stats, html_file)?;
+    }
+
     println!("✅ Quality analysis complete!");
     println!("📈 Sample: {}", stats.sample_name);
     println!("📚 Total reads: {}", stats.total_reads);
     println!("🧬 Total bases: {}", stats.total_bases);
+    println!(
+        "📏 Read length: {}-{} (mean {:.1}, N50 {})",
+        stats.length_distribution.min_length,
+        stats.length_distribution.max_length,
+        stats.length_distribution.mean_length,
+        stats.length_distribution.n50
+    );
     println!("🔬 GC content: {:.2}%", stats.gc_content);
+    println!(
+        "📐 GC distribution: mode {:.0}%, {:.1}% deviation from normal",
+        stats.gc_distribution.mode_gc, stats.gc_distribution.deviation_from_normal
+    );
+    if stats.gc_distribution.multimodal {
+        let peaks: Vec<String> = stats
+            .gc_distribution
+            .peaks
+            .iter()
+            .map(|peak| format!("{}%", peak.gc_percent))
+            .collect();
+        println!("   ⚠️ Multimodal GC profile, peaks at {}", peaks.join(", "));
+    }
     println!("⭐ Mean quality: {:.2}", stats.mean_quality);
     println!("🎯 Q30 bases: {:.2}%", stats.q30_bases);
     println!("🌀 Complexity: {:.2}", stats.complexity_score);
+    if let (Some(first), Some(last)) = (
+        stats.per_position_quality.first(),
+        stats.per_position_quality.last(),
+    ) {
+        println!(
+            "📉 Per-position quality: {} cycles, median Q{:.0} at cycle {} → Q{:.0} at cycle {}",
+            stats.per_position_quality.len(),
+            first.median,
+            first.position,
+            last.median,
+            last.position
+        );
+    }
+    if let Some(peak_n) = stats
+        .per_position_composition
+        .iter()
+        .max_by(|a, b| a.n.total_cmp(&b.n))
+    {
+        println!("🧩 Peak N content: {:.2}% at cycle {}", peak_n.n, peak_n.position);
+    }
+    println!(
+        "♻️ Reads remaining if deduplicated: {:.2}%",
+        stats.duplication.percent_remaining_if_deduplicated
+    );
+    if stats.tile_quality.reads_with_coordinates > 0 {
+        let flagged = stats.tile_quality.flagged_tiles();
+        println!(
+            "🗺️ Tiles profiled: {} across {} lane(s), {} flagged",
+            stats.tile_quality.tiles.len(),
+            stats.tile_quality.lanes.len(),
+            flagged.len()
+        );
+        for (lane, tile) in flagged {
+            println!("   ⚠️ Lane {} tile {} below lane median", lane, tile);
+        }
+    }
+    for content in &stats.adapter_content {
+        println!(
+            "🔌 {} adapter: {:.2}% of reads by cycle {}",
+            content.adapter,
+            content.max_percent,
+            content.percent_by_position.len()
+        );
+    }
+    if stats.overrepresented_sequences.is_empty() {
+        println!("🔁 No overrepresented sequences");
+    } else {
+        println!("🔁 Overrepresented sequences: {}", stats.overrepresented_sequences.len());
+        for hit in stats.overrepresented_sequences.iter().take(5) {
+            println!(
+                "   {:.2}% ({}) {} [{}]",
+                hit.percentage, hit.count, hit.sequence, hit.possible_source
+            );
+        }
+    }
+    println!("🚦 Module verdicts: {}", overall_verdict(&stats.verdicts));
+    for verdict in &stats.verdicts {
+        println!("   {} {} ({})", verdict.verdict, verdict.module.title(), verdict.detail);
+    }
     println!("💾 Results saved to: {}", output_file.display());
+    if let Some(ref html_file) = html_file {
+        println!("📄 HTML report saved to: {}", html_file.display());
+    }
 
     Ok(())
 }
\ No newline at end of file
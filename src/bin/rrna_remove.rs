@@ -10,6 +10,7 @@
 
 use anyhow::Result;
 use biometal_qc_tools::rrna::RrnaRemover;
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
 use clap::{Arg, Command};
 use serde_json;
 use std::path::PathBuf;
@@ -44,6 +45,12 @@ fn main() -> Result<()> {
                 .help("Output rRNA-filtered FASTQ file")
                 .required(false),
         )
+        .arg(
+            Arg::new("failed_out")
+                .long("failed-out")
+                .value_name("FASTQ")
+                .help("Write removed reads here, with reason=<tag> appended to the header"),
+        )
         .arg(
             Arg::new("stats")
                 .short('s')
@@ -87,13 +94,21 @@ fn main() -> Result<()> {
                 .help("Verbose output")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_path = PathBuf::from(matches.get_one::<String>("input").unwrap());
     let database_path = PathBuf::from(matches.get_one::<String>("database").unwrap());
     let output_path = matches.get_one::<String>("output").map(PathBuf::from);
+    let failed_path = matches.get_one::<String>("failed_out").map(PathBuf::from);
     let stats_path = PathBuf::from(matches.get_one::<String>("stats").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let minimizer_length: usize = matches.get_one::<String>("minimizer_length").unwrap().parse()?;
     let alignment_threshold: f64 = matches.get_one::<String>("alignment_threshold").unwrap().parse()?;
     let kmer_size: usize = matches.get_one::<String>("kmer_size").unwrap().parse()?;
@@ -117,6 +132,9 @@ fn main() -> Result<()> {
         } else {
             println!("  Output file: None (statistics only)");
         }
+        if let Some(ref failed_path) = failed_path {
+            println!("  Removed reads file: {}", failed_path.display());
+        }
         println!("  Stats file: {}", stats_path.display());
         println!("  Minimizer length: {}", minimizer_length);
         println!("  Alignment threshold: {:.2}", alignment_threshold);
@@ -165,7 +183,7 @@ fn main() -> Result<()> {
     }
 
     // Process the FASTQ file with advanced biometal algorithms
-    let stats = remover.process_fastq(&input_path, &database_path, output_path.as_ref())?;
+    let stats = remover.process_fastq(&input_path, &database_path, output_path.as_ref(), failed_path.as_ref())?;
 
     if verbose {
         println!("✅ rRNA removal pipeline completed!");
@@ -221,6 +239,14 @@ fn main() -> Result<()> {
     let stats_json = serde_json::to_string_pretty(&stats)?;
     std::fs::write(&stats_path, stats_json)?;
 
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_path);
+        let metrics = stats.multiqc_metrics();
+        let written = write_general_stats("biometal-rrna-remove", "Ribosomal RNA removal", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
     if verbose {
         println!("💾 Detailed statistics saved to: {}", stats_path.display());
     }
@@ -0,0 +1,163 @@
+//! Biometal Tile Filter Tool
+//!
+//! Remove reads from bad Illumina tiles (bubbles, flowcell damage).
+//! Tiles are chosen by per-tile quality outlier detection, an explicit list, or both.
+
+use anyhow::Result;
+use biometal_qc_tools::encoding::resolve_encoding;
+use biometal_qc_tools::tiles::{parse_tile_list, TileFilter, TileOutlierRule};
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
+use clap::{Arg, Command};
+use std::path::PathBuf;
+
+fn main() -> Result<()> {
+    let matches = Command::new("biometal-tile-filter")
+        .version("0.1.0")
+        .about("Remove reads from outlier or listed Illumina tiles")
+        .author("Megan Johnson")
+        .arg(
+            Arg::new("input")
+                .short('i')
+                .long("input")
+                .value_name("FASTQ")
+                .help("Input FASTQ file")
+                .required(true),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FASTQ")
+                .help("Output FASTQ file without reads from excluded tiles")
+                .required(true),
+        )
+        .arg(
+            Arg::new("stats")
+                .short('s')
+                .long("stats")
+                .value_name("JSON")
+                .help("Output tile filtering statistics (JSON)")
+                .default_value("tile_filter_stats.json"),
+        )
+        .arg(
+            Arg::new("tiles")
+                .long("tiles")
+                .value_name("LIST")
+                .help("Comma-separated tiles to exclude: TILE (any lane) or LANE:TILE"),
+        )
+        .arg(
+            Arg::new("no_outlier_detection")
+                .long("no-outlier-detection")
+                .help("Only exclude tiles given with --tiles")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("max_mean_drop")
+                .long("max-mean-drop")
+                .value_name("PHRED")
+                .help("Flag tiles whose mean quality is this far below the lane median")
+                .default_value("2.0"),
+        )
+        .arg(
+            Arg::new("max_cycle_drop")
+                .long("max-cycle-drop")
+                .value_name("PHRED")
+                .help("Flag tiles that drop this far below the lane median at any cycle")
+                .default_value("5.0"),
+        )
+        .arg(
+            Arg::new("phred_offset")
+                .long("phred-offset")
+                .value_name("OFFSET")
+                .help("Quality score encoding: auto (detect from first reads), 33 or 64")
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
+        .get_matches();
+
+    // Parse arguments
+    let input_path = PathBuf::from(matches.get_one::<String>("input").unwrap());
+    let output_path = PathBuf::from(matches.get_one::<String>("output").unwrap());
+    let stats_path = PathBuf::from(matches.get_one::<String>("stats").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
+    let explicit_tiles = match matches.get_one::<String>("tiles") {
+        Some(list) => parse_tile_list(list)?,
+        None => Vec::new(),
+    };
+    let detect_outliers = !matches.get_flag("no_outlier_detection");
+    let max_mean_drop: f64 = matches.get_one::<String>("max_mean_drop").unwrap().parse()?;
+    let max_cycle_drop: f64 = matches.get_one::<String>("max_cycle_drop").unwrap().parse()?;
+    let phred_offset = matches.get_one::<String>("phred_offset").unwrap();
+
+    // Validate input file exists
+    if !input_path.exists() {
+        anyhow::bail!("Input file does not exist: {}", input_path.display());
+    }
+
+    if !detect_outliers && explicit_tiles.is_empty() {
+        anyhow::bail!("Nothing to filter: pass --tiles or enable outlier detection");
+    }
+
+    let outlier_rule = if detect_outliers {
+        Some(TileOutlierRule {
+            max_mean_drop,
+            max_cycle_drop,
+        })
+    } else {
+        None
+    };
+
+    println!("🗺️ Biometal Tile Filter");
+    println!("Input: {}", input_path.display());
+    println!("Output: {}", output_path.display());
+    if let Some(rule) = outlier_rule {
+        println!(
+            "Outlier rule: mean drop > {:.1}, cycle drop > {:.1} below lane median",
+            rule.max_mean_drop, rule.max_cycle_drop
+        );
+    }
+    if !explicit_tiles.is_empty() {
+        println!("Explicit tiles: {}", explicit_tiles.len());
+    }
+
+    // Resolve quality encoding (detected from the first reads unless overridden)
+    let encoding = resolve_encoding(&input_path, phred_offset)?;
+    println!("Quality encoding: {}", encoding);
+
+    // Filter reads
+    let filter = TileFilter::new(outlier_rule, explicit_tiles, encoding);
+    let stats = filter.filter_reads(&input_path, &output_path)?;
+
+    // Write statistics
+    let stats_json = serde_json::to_string_pretty(&stats)?;
+    std::fs::write(&stats_path, stats_json)?;
+
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_path);
+        let metrics = stats.multiqc_metrics();
+        let written = write_general_stats("biometal-tile-filter", "Bad-tile read removal", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
+    println!("📊 Tile Filtering Results:");
+    println!("  Total reads: {}", stats.total_reads);
+    println!("  Reads kept: {}", stats.reads_kept);
+    println!("  Reads removed: {} ({:.2}%)", stats.reads_removed, stats.removal_rate);
+    println!("  Reads without tile coordinates (kept): {}", stats.reads_without_coordinates);
+    for excluded in &stats.excluded_tiles {
+        let lane = excluded.lane.map_or("*".to_string(), |l| l.to_string());
+        println!(
+            "  Excluded lane {} tile {} ({}): {} reads",
+            lane, excluded.tile, excluded.source, excluded.reads_removed
+        );
+    }
+    println!("💾 Statistics saved to: {}", stats_path.display());
+
+    Ok(())
+}
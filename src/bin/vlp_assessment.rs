@@ -4,6 +4,7 @@
 
 use anyhow::Result;
 use biometal_qc_tools::vlp::VlpAssessor;
+use biometal_qc_tools::multiqc::{sample_name_from_path, write_general_stats, MultiqcMetrics};
 use clap::{Arg, Command};
 use std::path::PathBuf;
 
@@ -57,11 +58,26 @@ fn main() -> Result<()> {
                 .help("Minimum read length")
                 .default_value("50"),
         )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .long("threads")
+                .value_name("N")
+                .help("Worker threads (output is identical for any thread count)")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("multiqc")
+                .long("multiqc")
+                .value_name("JSON")
+                .help("Also write MultiQC custom content (*_mqc.json)"),
+        )
         .get_matches();
 
     // Parse arguments
     let input_file = PathBuf::from(matches.get_one::<String>("input").unwrap());
     let output_file = PathBuf::from(matches.get_one::<String>("output").unwrap());
+    let multiqc_path = matches.get_one::<String>("multiqc").map(PathBuf::from);
     let min_complexity: f64 = matches
         .get_one::<String>("min_complexity")
         .unwrap()
@@ -72,11 +88,13 @@ fn main() -> Result<()> {
         .get_one::<String>("min_length")
         .unwrap()
         .parse()?;
+    let threads: usize = matches.get_one::<String>("threads").unwrap().parse()?;
 
     println!("🦠 Biometal VLP Assessment Tool");
     println!("Input: {}", input_file.display());
     println!("Output: {}", output_file.display());
     println!("Min Length: {}", min_length);
+    println!("Threads: {}", threads);
 
     // Validate input file exists
     if !input_file.exists() {
@@ -84,7 +102,8 @@ fn main() -> Result<()> {
     }
 
     // Create VLP assessor
-    let assessor = VlpAssessor::new(min_complexity, (gc_min, gc_max), min_length);
+    let mut assessor = VlpAssessor::new(min_complexity, (gc_min, gc_max), min_length);
+    assessor.threads = threads;
 
     // Assess VLP success
     println!("🧬 Assessing VLP success metrics...");
@@ -97,12 +116,29 @@ fn main() -> Result<()> {
     let json_output = serde_json::to_string_pretty(&report)?;
     std::fs::write(&output_file, &json_output)?;
 
+    // MultiQC custom content
+    if let Some(ref multiqc_path) = multiqc_path {
+        let sample = sample_name_from_path(&input_file);
+        let metrics = report.multiqc_metrics();
+        let written = write_general_stats("biometal-vlp-assessment", "VLP success metrics", &sample, metrics, multiqc_path)?;
+        println!("📊 MultiQC stats saved to: {}", written.display());
+    }
+
     println!("✅ VLP assessment complete!");
     println!("📈 Sample: {}", report.sample_name);
     println!("🔬 GC distribution score: {:.3}", report.gc_distribution_score);
     println!("🌀 Complexity diversity: {:.3}", report.complexity_diversity);
     println!("⚖️ Compositional evenness: {:.3}", report.compositional_evenness);
     println!("🎯 Overall VLP score: {:.3}", report.vlp_success_score);
+    if report.gc_distribution.multimodal {
+        let peaks: Vec<String> = report
+            .gc_distribution
+            .peaks
+            .iter()
+            .map(|peak| format!("{}%", peak.gc_percent))
+            .collect();
+        println!("⚠️ Multimodal GC profile (peaks at {}): possible bacterial or host contamination", peaks.join(", "));
+    }
     println!(
         "{}",
         if successful {
@@ -0,0 +1,140 @@
+//! Before/after comparison of a processing step
+//!
+//! Both FASTQ files go through the same quality analysis, and the headline
+//! metrics, length distributions and per-position quality profiles are paired
+//! up so the effect of a trimming or filtering step can be read off directly.
+
+use crate::distributions::LengthDistribution;
+use crate::QcStats;
+use serde::{Deserialize, Serialize};
+
+/// One metric measured on the input and on the output
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub before: f64,
+    pub after: f64,
+    pub delta: f64, // after - before
+}
+
+impl MetricDelta {
+    pub fn new(before: f64, after: f64) -> Self {
+        Self {
+            before,
+            after,
+            delta: after - before,
+        }
+    }
+}
+
+/// Quality at one cycle before and after processing (None past the longest read)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionQualityDelta {
+    pub position: usize, // 1-based cycle number
+    pub mean_before: Option<f64>,
+    pub mean_after: Option<f64>,
+    pub median_before: Option<f64>,
+    pub median_after: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QcComparison {
+    pub before_sample: String,
+    pub after_sample: String,
+    pub reads_before: u64,
+    pub reads_after: u64,
+    pub read_retention: f64, // % of input reads present in the output
+    pub bases_before: u64,
+    pub bases_after: u64,
+    pub base_retention: f64, // % of input bases present in the output
+    pub mean_quality: MetricDelta,
+    pub q30_bases: MetricDelta,
+    pub gc_content: MetricDelta,
+    pub mean_length: MetricDelta,
+    pub n50: MetricDelta,
+    pub length_before: LengthDistribution,
+    pub length_after: LengthDistribution,
+    pub per_position_quality: Vec<PositionQualityDelta>,
+}
+
+/// Pair up quality statistics from a processing step's input and output
+pub fn compare_stats(before: &QcStats, after: &QcStats) -> QcComparison {
+    let positions = before.per_position_quality.len().max(after.per_position_quality.len());
+    let per_position_quality = (0..positions)
+        .map(|index| {
+            let before = before.per_position_quality.get(index);
+            let after = after.per_position_quality.get(index);
+            PositionQualityDelta {
+                position: index + 1,
+                mean_before: before.map(|p| p.mean),
+                mean_after: after.map(|p| p.mean),
+                median_before: before.map(|p| p.median),
+                median_after: after.map(|p| p.median),
+            }
+        })
+        .collect();
+
+    QcComparison {
+        before_sample: before.sample_name.clone(),
+        after_sample: after.sample_name.clone(),
+        reads_before: before.total_reads,
+        reads_after: after.total_reads,
+        read_retention: percent(after.total_reads, before.total_reads),
+        bases_before: before.total_bases,
+        bases_after: after.total_bases,
+        base_retention: percent(after.total_bases, before.total_bases),
+        mean_quality: MetricDelta::new(before.mean_quality, after.mean_quality),
+        q30_bases: MetricDelta::new(before.q30_bases, after.q30_bases),
+        gc_content: MetricDelta::new(before.gc_content, after.gc_content),
+        mean_length: MetricDelta::new(
+            before.length_distribution.mean_length,
+            after.length_distribution.mean_length,
+        ),
+        n50: MetricDelta::new(
+            before.length_distribution.n50 as f64,
+            after.length_distribution.n50 as f64,
+        ),
+        length_before: before.length_distribution.clone(),
+        length_after: after.length_distribution.clone(),
+        per_position_quality,
+    }
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total > 0 {
+        part as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::QualityEncoding;
+    use crate::quality::QualityAccumulator;
+    use biometal::FastqRecord;
+
+    fn stats(name: &str, reads: usize, length: usize, quality: u8) -> QcStats {
+        let mut accumulator = QualityAccumulator::new(name, QualityEncoding::Phred33, 1);
+        for _ in 0..reads {
+            let sequence = b"ACGT".repeat(length / 4);
+            accumulator.add(&FastqRecord::new("r".to_string(), sequence, vec![quality; length]));
+        }
+        accumulator.finish()
+    }
+
+    #[test]
+    fn test_retention_and_deltas() {
+        let before = stats("raw", 4, 40, b'5');
+        let after = stats("trimmed", 2, 32, b'?');
+        let comparison = compare_stats(&before, &after);
+
+        assert_eq!(comparison.read_retention, 50.0);
+        assert_eq!(comparison.base_retention, 40.0);
+        assert_eq!(comparison.mean_quality.delta, 10.0);
+        assert_eq!(comparison.mean_length.after, 32.0);
+        assert_eq!(comparison.per_position_quality.len(), 40);
+        assert_eq!(comparison.per_position_quality[39].mean_after, None);
+        assert_eq!(comparison.per_position_quality[0].median_before, Some(20.0));
+    }
+}
@@ -0,0 +1,346 @@
+//! Low-complexity detection for biometal-quality-filter
+//!
+//! - Trinucleotide entropy: Shannon entropy of a read's overlapping triplets,
+//!   scaled to 0-1 as prinseq-lite does (its `-lc_method entropy -lc_threshold 70`
+//!   is 0.7 here)
+//! - DUST: symmetric DUST (Morgulis et al. 2006) as implemented by sdust. An
+//!   interval of `l` triplets scores `r = sum c(c-1)/2` and is low complexity
+//!   at level `T` when `10r > T(l-1)`, i.e. its mean repeat score exceeds T/10.
+//!   Only perfect intervals are masked: ones that score above the level and
+//!   contain no sub-interval scoring higher, so flanking sequence next to a
+//!   repeat is left alone. Window 64 and level 20 are the dustmasker / sdust
+//!   defaults.
+
+use crate::trimming::is_ambiguous;
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// How DUST marks low-complexity bases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskMode {
+    Hard, // Replace with N
+    Soft, // Lowercase
+}
+
+impl MaskMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MaskMode::Hard => "N",
+            MaskMode::Soft => "soft",
+        }
+    }
+}
+
+impl FromStr for MaskMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "n" | "hard" => Ok(MaskMode::Hard),
+            "soft" => Ok(MaskMode::Soft),
+            other => anyhow::bail!("Unknown mask mode '{}' (expected n or soft)", other),
+        }
+    }
+}
+
+/// DUST low-complexity masker
+#[derive(Debug, Clone)]
+pub struct DustMasker {
+    pub window: usize,  // Bases
+    pub threshold: f64, // Level T: mask intervals whose mean repeat score exceeds T/10
+    pub mode: MaskMode,
+}
+
+/// Candidate interval scoring above the level (sdust's perfect interval)
+#[derive(Debug, Clone, Copy)]
+struct PerfectInterval {
+    start: usize,
+    finish: usize, // Exclusive, in bases
+    repeats: usize,
+    length: usize, // Triplets minus one
+}
+
+/// Sliding window of triplet codes with the counts sdust keeps alongside it
+struct DustWindow {
+    triplets: VecDeque<usize>,
+    window_counts: [usize; 64],
+    window_repeats: usize,
+    suffix_counts: [usize; 64], // Longest suffix with no triplet over the level on its own
+    suffix_repeats: usize,
+    suffix_length: usize,
+}
+
+impl DustWindow {
+    fn new() -> Self {
+        Self {
+            triplets: VecDeque::new(),
+            window_counts: [0; 64],
+            window_repeats: 0,
+            suffix_counts: [0; 64],
+            suffix_repeats: 0,
+            suffix_length: 0,
+        }
+    }
+}
+
+impl DustMasker {
+    pub fn new(threshold: f64, mode: MaskMode) -> Self {
+        Self {
+            window: 64,
+            threshold,
+            mode,
+        }
+    }
+
+    /// Low-complexity stretches of a sequence, merged and in order
+    pub fn intervals(&self, sequence: &[u8]) -> Vec<Range<usize>> {
+        let mut found: Vec<Range<usize>> = Vec::new();
+        let mut perfect: Vec<PerfectInterval> = Vec::new(); // Descending start
+        let mut window = DustWindow::new();
+        let mut run = 0usize; // Unambiguous bases since the last N
+        let mut triplet = 0;
+
+        for position in 0..=sequence.len() {
+            match sequence.get(position).and_then(|&base| base_code(base)) {
+                Some(code) => {
+                    run += 1;
+                    triplet = (triplet << 2 | code) & 63;
+                    if run < 3 {
+                        continue;
+                    }
+                    let start = run.saturating_sub(self.window) + position + 1 - run;
+                    save_masked(&mut found, &mut perfect, start);
+                    self.shift_window(&mut window, triplet);
+                    if self.scores_above(window.window_repeats, window.suffix_length) {
+                        self.find_perfect(&mut perfect, &window, start);
+                    }
+                }
+                None => {
+                    // Ambiguous bases split the read into independently scored runs
+                    let mut start = (run + 1).saturating_sub(self.window) + position + 1 - run;
+                    while !perfect.is_empty() {
+                        save_masked(&mut found, &mut perfect, start);
+                        start += 1;
+                    }
+                    window = DustWindow::new();
+                    run = 0;
+                    triplet = 0;
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Mask low-complexity bases in place, returning how many were masked
+    pub fn mask(&self, sequence: &mut [u8]) -> usize {
+        let intervals = self.intervals(sequence);
+        for interval in &intervals {
+            for base in &mut sequence[interval.clone()] {
+                *base = match self.mode {
+                    MaskMode::Hard => b'N',
+                    MaskMode::Soft => base.to_ascii_lowercase(),
+                };
+            }
+        }
+        intervals.iter().map(|interval| interval.len()).sum()
+    }
+
+    /// `10r > T l`, the level test for an interval of `length + 1` triplets
+    fn scores_above(&self, repeats: usize, length: usize) -> bool {
+        (repeats * 10) as f64 > self.threshold * length as f64
+    }
+
+    /// Add a triplet, dropping the oldest once the window is full and
+    /// shrinking the suffix until no triplet in it is over the level alone
+    fn shift_window(&self, window: &mut DustWindow, triplet: usize) {
+        if window.triplets.len() >= self.window.saturating_sub(2).max(1) {
+            if let Some(oldest) = window.triplets.pop_front() {
+                window.window_counts[oldest] -= 1;
+                window.window_repeats -= window.window_counts[oldest];
+                if window.suffix_length > window.triplets.len() {
+                    window.suffix_length -= 1;
+                    window.suffix_counts[oldest] -= 1;
+                    window.suffix_repeats -= window.suffix_counts[oldest];
+                }
+            }
+        }
+
+        window.triplets.push_back(triplet);
+        window.suffix_length += 1;
+        window.window_repeats += window.window_counts[triplet];
+        window.window_counts[triplet] += 1;
+        window.suffix_repeats += window.suffix_counts[triplet];
+        window.suffix_counts[triplet] += 1;
+
+        if (window.suffix_counts[triplet] * 10) as f64 > self.threshold * 2.0 {
+            loop {
+                let dropped = window.triplets[window.triplets.len() - window.suffix_length];
+                window.suffix_counts[dropped] -= 1;
+                window.suffix_repeats -= window.suffix_counts[dropped];
+                window.suffix_length -= 1;
+                if dropped == triplet {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Record intervals ending at the newest triplet that score above the level
+    /// and at least as high as every recorded interval inside them
+    fn find_perfect(&self, perfect: &mut Vec<PerfectInterval>, window: &DustWindow, start: usize) {
+        let size = window.triplets.len();
+        let mut counts = window.suffix_counts;
+        let mut repeats = window.suffix_repeats;
+        let (mut max_repeats, mut max_length) = (0, 0);
+
+        for first in (0..size - window.suffix_length).rev() {
+            let triplet = window.triplets[first];
+            repeats += counts[triplet];
+            counts[triplet] += 1;
+            let length = size - first - 1;
+            if !self.scores_above(repeats, length) {
+                continue;
+            }
+
+            let mut insert_at = 0;
+            while insert_at < perfect.len() && perfect[insert_at].start >= first + start {
+                let inner = perfect[insert_at];
+                if max_repeats == 0 || inner.repeats * max_length > max_repeats * inner.length {
+                    (max_repeats, max_length) = (inner.repeats, inner.length);
+                }
+                insert_at += 1;
+            }
+            if max_repeats == 0 || repeats * max_length >= max_repeats * length {
+                (max_repeats, max_length) = (repeats, length);
+                perfect.insert(
+                    insert_at,
+                    PerfectInterval {
+                        start: first + start,
+                        finish: size + 2 + start,
+                        repeats,
+                        length,
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Emit the leftmost perfect interval once intervals start falling out of the
+/// window at `start`, then forget those that have
+fn save_masked(found: &mut Vec<Range<usize>>, perfect: &mut Vec<PerfectInterval>, start: usize) {
+    let Some(&leftmost) = perfect.last() else {
+        return;
+    };
+    if leftmost.start >= start {
+        return;
+    }
+    push_merged(found, leftmost.start..leftmost.finish);
+    while perfect.last().is_some_and(|interval| interval.start < start) {
+        perfect.pop();
+    }
+}
+
+/// Shannon entropy of overlapping triplets, scaled to 0-1 by the most the
+/// read's triplet count allows (0 for reads with fewer than two triplets)
+pub fn trinucleotide_entropy(sequence: &[u8]) -> f64 {
+    let mut counts = [0u32; 64];
+    let mut total = 0u32;
+    for window in sequence.windows(3) {
+        if window.iter().any(|&base| is_ambiguous(base)) {
+            continue;
+        }
+        counts[triplet_code(window)] += 1;
+        total += 1;
+    }
+    if total < 2 {
+        return 0.0;
+    }
+
+    let entropy: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum();
+    entropy / (total.min(64) as f64).log2()
+}
+
+fn triplet_code(triplet: &[u8]) -> usize {
+    triplet.iter().fold(0, |code, &base| code * 4 + base_code(base).unwrap_or(3))
+}
+
+/// 2-bit code of an unambiguous base
+fn base_code(base: u8) -> Option<usize> {
+    match base.to_ascii_uppercase() {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' => Some(3),
+        _ => None,
+    }
+}
+
+fn push_merged(intervals: &mut Vec<Range<usize>>, interval: Range<usize>) {
+    match intervals.last_mut() {
+        Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+        _ => intervals.push(interval),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entropy_and_dust() {
+        assert_eq!(trinucleotide_entropy(&[b'A'; 50]), 0.0);
+        assert!(trinucleotide_entropy(b"ACGTTGCAAGCTTCGAGGATCCTAGCATGCAGTCGACCATGGTACCGG") > 0.9);
+
+        // The poly-A run (61 bp with the flank's leading A) scores 29.5 and is masked; the flanks are not
+        let flank = b"ACGTTGCAAGCTTCGAGGATCC";
+        let mut read = flank.to_vec();
+        read.extend_from_slice(&[b'A'; 60]);
+        read.extend_from_slice(flank);
+
+        let masker = DustMasker::new(20.0, MaskMode::Hard);
+        assert_eq!(masker.intervals(&read), vec![22..83]);
+        assert!(masker.intervals(flank).is_empty());
+
+        let mut soft = read.clone();
+        let soft_masker = DustMasker::new(20.0, "soft".parse().unwrap());
+        assert_eq!(soft_masker.mask(&mut soft), 61);
+        assert_eq!(&soft[22..83], &[b'a'; 61][..]);
+        assert_eq!(&soft[..22], &flank[..]);
+        assert_eq!(&soft[83..], &flank[1..]);
+    }
+
+    #[test]
+    fn test_dust_masks_short_period_repeats_only() {
+        let left = b"TGCATCGGATCTAGCAAGTCCGATTACGGTCAAGCTTGGA";
+        let right = b"TTACCTGAGTTCAGGACTCATGCGTATCCGAAGTGCTTAC";
+        let masker = DustMasker::new(20.0, MaskMode::Hard);
+        assert!(masker.intervals(&[&left[..], &right[..]].concat()).is_empty());
+
+        // AC, CAG and ACGT repeats score 7-14, well above level 20 (a score of 2)
+        for unit in [&b"AC"[..], b"CAG", b"ACGT"] {
+            let mut read = left.to_vec();
+            read.extend(unit.iter().cycle().take(60));
+            read.extend_from_slice(right);
+            assert_eq!(masker.intervals(&read), vec![40..100], "{}", String::from_utf8_lossy(unit));
+        }
+
+        // An N splits the repeat; each side is scored on its own
+        let mut split = left.to_vec();
+        split.extend(b"AC".iter().cycle().take(30));
+        split.push(b'N');
+        split.extend(b"AC".iter().cycle().take(29));
+        split.extend_from_slice(right);
+        assert_eq!(masker.intervals(&split), vec![40..70, 71..100]);
+    }
+}
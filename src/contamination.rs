@@ -4,9 +4,10 @@
 //! - pattern_match: For known contamination sequences
 //! - base_counting: For composition-based detection
 
+use crate::parallel::{accumulate_fastq, DEFAULT_BATCH_SIZE};
 use anyhow::Result;
-use biometal::io::{DataSource, FastqStream};
 use biometal::operations::has_pattern;
+use biometal::FastqRecord;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -25,6 +26,7 @@ pub struct ContaminationScreener {
     pub phix_threshold: f64,
     pub vector_threshold: f64,
     pub min_length: usize,
+    pub threads: usize,
 }
 
 impl Default for ContaminationScreener {
@@ -33,6 +35,7 @@ impl Default for ContaminationScreener {
             phix_threshold: 0.1, // 0.1% PhiX threshold
             vector_threshold: 0.05, // 0.05% vector threshold
             min_length: 50, // Minimum read length
+            threads: 1,
         }
     }
 }
@@ -43,13 +46,14 @@ impl ContaminationScreener {
             phix_threshold,
             vector_threshold,
             min_length,
+            threads: 1,
         }
     }
 
     /// Comprehensive PhiX174 contamination sequences
     /// Uses longer, more specific patterns from PhiX174 genome for better sensitivity
     /// Replaces short snippets with representative 31-mers from different genome regions
-    fn get_phix_patterns() -> Vec<&'static str> {
+    pub fn get_phix_patterns() -> Vec<&'static str> {
         vec![
             // Start region (positions 1-31)
             "GAGTTTTATCGCTTCCATGACGCAGAAGTTA",
@@ -88,7 +92,7 @@ impl ContaminationScreener {
     /// Comprehensive vector contamination sequences
     /// Representative 31-mers from major cloning vectors, expression vectors, and viral vectors
     /// Based on UniVec database used by BBDuk in the lab pipeline
-    fn get_vector_patterns() -> Vec<&'static str> {
+    pub fn get_vector_patterns() -> Vec<&'static str> {
         vec![
             // pBR322 (most common cloning vector) - multiple regions
             "TTCTCATGTTTGACAGCTTATCATCGATAAG",
@@ -174,83 +178,102 @@ impl ContaminationScreener {
             .unwrap_or("unknown")
             .to_string();
 
-        // Get contamination patterns
-        let phix_patterns = Self::get_phix_patterns();
-        let vector_patterns = Self::get_vector_patterns();
+        // Batches of records are processed on `threads` workers and merged in order
+        let accumulator = accumulate_fastq(&fastq_path, self.threads, DEFAULT_BATCH_SIZE, || {
+            self.accumulator(&sample_name)
+        })?;
 
-        // Initialize counters
-        let mut total_reads = 0u64;
-        let mut phix_reads = 0u64;
-        let mut vector_reads = 0u64;
-
-        // Create biometal data source and stream
-        let data_source = DataSource::from_path(&fastq_path);
-        let fastq_stream = FastqStream::new(data_source)?;
+        Ok(accumulator.finish())
+    }
 
-        // Process reads using biometal streaming
-        for record_result in fastq_stream {
-            let record = record_result?;
+    /// Empty accumulator using this screener's settings
+    pub fn accumulator(&self, sample_name: &str) -> ContaminationAccumulator {
+        ContaminationAccumulator::new(sample_name, self.min_length)
+    }
 
-            // Skip empty records or records below minimum length
-            if record.is_empty() || record.sequence.len() < self.min_length {
-                continue;
-            }
+    /// Check if contamination levels are within acceptable thresholds
+    pub fn is_contamination_acceptable(&self, report: &ContaminationReport) -> bool {
+        report.phix_percentage <= self.phix_threshold
+            && report.vector_percentage <= self.vector_threshold
+    }
+}
 
-            total_reads += 1;
+/// Streaming contamination counts that can be merged across shards
+#[derive(Debug, Clone)]
+pub struct ContaminationAccumulator {
+    pub sample_name: String,
+    pub min_length: usize,
+    phix_patterns: Vec<&'static str>,
+    vector_patterns: Vec<&'static str>,
+    total_reads: u64,
+    phix_reads: u64,
+    vector_reads: u64,
+}
 
-            // Check for PhiX contamination using biometal pattern matching
-            let mut is_phix_contaminated = false;
-            for pattern in &phix_patterns {
-                if has_pattern(&record.sequence, pattern.as_bytes()) {
-                    is_phix_contaminated = true;
-                    break;
-                }
-            }
-            if is_phix_contaminated {
-                phix_reads += 1;
-            }
+impl ContaminationAccumulator {
+    pub fn new(sample_name: &str, min_length: usize) -> Self {
+        Self {
+            sample_name: sample_name.to_string(),
+            min_length,
+            phix_patterns: ContaminationScreener::get_phix_patterns(),
+            vector_patterns: ContaminationScreener::get_vector_patterns(),
+            total_reads: 0,
+            phix_reads: 0,
+            vector_reads: 0,
+        }
+    }
 
-            // Check for vector contamination using biometal pattern matching
-            let mut is_vector_contaminated = false;
-            for pattern in &vector_patterns {
-                if has_pattern(&record.sequence, pattern.as_bytes()) {
-                    is_vector_contaminated = true;
-                    break;
-                }
-            }
-            if is_vector_contaminated {
-                vector_reads += 1;
-            }
+    /// Screen one record; empty reads and reads below `min_length` are skipped
+    pub fn add(&mut self, record: &FastqRecord) {
+        if record.is_empty() || record.sequence.len() < self.min_length {
+            return;
         }
 
-        // Calculate percentages
-        let phix_percentage = if total_reads > 0 {
-            (phix_reads as f64 / total_reads as f64) * 100.0
-        } else {
-            0.0
-        };
+        self.total_reads += 1;
 
-        let vector_percentage = if total_reads > 0 {
-            (vector_reads as f64 / total_reads as f64) * 100.0
-        } else {
-            0.0
-        };
+        // Check for PhiX contamination using biometal pattern matching
+        if self
+            .phix_patterns
+            .iter()
+            .any(|pattern| has_pattern(&record.sequence, pattern.as_bytes()))
+        {
+            self.phix_reads += 1;
+        }
 
-        let report = ContaminationReport {
-            sample_name,
-            total_reads,
-            phix_reads,
-            vector_reads,
-            phix_percentage,
-            vector_percentage,
-        };
+        // Check for vector contamination using biometal pattern matching
+        if self
+            .vector_patterns
+            .iter()
+            .any(|pattern| has_pattern(&record.sequence, pattern.as_bytes()))
+        {
+            self.vector_reads += 1;
+        }
+    }
 
-        Ok(report)
+    /// Fold in counts from another shard
+    pub fn merge(&mut self, other: Self) {
+        self.total_reads += other.total_reads;
+        self.phix_reads += other.phix_reads;
+        self.vector_reads += other.vector_reads;
     }
 
-    /// Check if contamination levels are within acceptable thresholds
-    pub fn is_contamination_acceptable(&self, report: &ContaminationReport) -> bool {
-        report.phix_percentage <= self.phix_threshold
-            && report.vector_percentage <= self.vector_threshold
+    /// Calculate percentages
+    pub fn finish(&self) -> ContaminationReport {
+        let percentage = |reads: u64| {
+            if self.total_reads > 0 {
+                (reads as f64 / self.total_reads as f64) * 100.0
+            } else {
+                0.0
+            }
+        };
+
+        ContaminationReport {
+            sample_name: self.sample_name.clone(),
+            total_reads: self.total_reads,
+            phix_reads: self.phix_reads,
+            vector_reads: self.vector_reads,
+            phix_percentage: percentage(self.phix_reads),
+            vector_percentage: percentage(self.vector_reads),
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,155 @@
+//! Bounded-memory counting
+//!
+//! FastQC-style sampling: the first `capacity` distinct keys are tracked and
+//! every later occurrence of a tracked key is still counted, so memory stays
+//! constant regardless of how many reads are streamed.
+//!
+//! Counters built over consecutive shards of a file can be merged in order;
+//! the result matches a single pass unless a shard hit the capacity limit.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Count and first-seen position of a tracked key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackedCount {
+    pub count: u64,
+    pub first_seen: u64, // observation index when the key was first tracked
+}
+
+/// Counter that tracks at most `capacity` distinct keys
+#[derive(Debug, Clone)]
+pub struct BoundedCounter<K> {
+    capacity: usize,
+    counts: HashMap<K, TrackedCount>,
+    observations: u64,
+    observations_at_capacity: Option<u64>,
+}
+
+impl<K: Hash + Eq> BoundedCounter<K> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            counts: HashMap::new(),
+            observations: 0,
+            observations_at_capacity: None,
+        }
+    }
+
+    /// Record one observation; returns false if the key could not be tracked
+    pub fn add(&mut self, key: K) -> bool {
+        let index = self.observations;
+        self.observations += 1;
+
+        if let Some(tracked) = self.counts.get_mut(&key) {
+            tracked.count += 1;
+            return true;
+        }
+
+        if self.counts.len() >= self.capacity {
+            return false;
+        }
+
+        self.counts.insert(key, TrackedCount { count: 1, first_seen: index });
+        if self.counts.len() == self.capacity {
+            self.observations_at_capacity = Some(self.observations);
+        }
+        true
+    }
+
+    /// Fold in a counter built over the observations that follow this one
+    ///
+    /// Keys are taken in the order `other` first saw them, so a full counter
+    /// keeps the same keys a single pass would have kept.
+    pub fn merge(&mut self, other: Self) {
+        let offset = self.observations;
+        let mut entries: Vec<(K, TrackedCount)> = other.counts.into_iter().collect();
+        entries.sort_by_key(|(_, tracked)| tracked.first_seen);
+
+        for (key, tracked) in entries {
+            if let Some(existing) = self.counts.get_mut(&key) {
+                existing.count += tracked.count;
+                continue;
+            }
+
+            if self.counts.len() >= self.capacity {
+                continue;
+            }
+
+            self.counts.insert(
+                key,
+                TrackedCount {
+                    count: tracked.count,
+                    first_seen: offset + tracked.first_seen,
+                },
+            );
+            if self.counts.len() == self.capacity && self.observations_at_capacity.is_none() {
+                self.observations_at_capacity = Some(offset + tracked.first_seen + 1);
+            }
+        }
+
+        self.observations += other.observations;
+        if self.observations_at_capacity.is_none() {
+            self.observations_at_capacity = other.observations_at_capacity.map(|at| offset + at);
+        }
+    }
+
+    /// Tracked keys with their counts (unordered)
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &TrackedCount)> {
+        self.counts.iter()
+    }
+
+    /// Number of distinct keys being tracked
+    pub fn tracked(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Total observations, tracked or not
+    pub fn observations(&self) -> u64 {
+        self.observations
+    }
+
+    /// Observations seen when the counter filled up (all observations if it never did)
+    pub fn observations_at_capacity(&self) -> u64 {
+        self.observations_at_capacity.unwrap_or(self.observations)
+    }
+
+    /// Whether new distinct keys are being ignored
+    pub fn is_saturated(&self) -> bool {
+        self.observations_at_capacity.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_matches_single_pass() {
+        let keys = ["a", "b", "a", "c", "b", "a", "d"];
+
+        let mut single = BoundedCounter::new(3);
+        for key in keys {
+            single.add(key);
+        }
+
+        let mut first = BoundedCounter::new(3);
+        let mut second = BoundedCounter::new(3);
+        for key in &keys[..3] {
+            first.add(*key);
+        }
+        for key in &keys[3..] {
+            second.add(*key);
+        }
+        first.merge(second);
+
+        fn sorted(counter: &BoundedCounter<&'static str>) -> Vec<(&'static str, TrackedCount)> {
+            let mut entries: Vec<_> = counter.iter().map(|(k, t)| (*k, *t)).collect();
+            entries.sort_by_key(|(key, _)| *key);
+            entries
+        }
+        assert_eq!(sorted(&first), sorted(&single));
+        assert_eq!(first.observations(), single.observations());
+        assert_eq!(first.observations_at_capacity(), single.observations_at_capacity());
+    }
+}
@@ -0,0 +1,537 @@
+//! Read length, GC content and expected-errors distributions
+//!
+//! Exact per-length counts are kept while streaming (one entry per distinct
+//! length, so memory is bounded by the read length) and summarised into
+//! min/max/mean/N50 plus a FastQC-style binned histogram.
+//!
+//! Per-read GC is binned at 1% resolution and compared with a normal curve
+//! (FastQC "per sequence GC content"). As in FastQC's GCModel, each read is
+//! spread over every bin its GC count can round into, so read lengths that do
+//! not divide evenly into 100 bins leave no empty teeth in the histogram.
+//! Separated peaks are reported so that bacterial or host contamination in a
+//! VLP prep shows up as a multimodal profile.
+//!
+//! Expected errors per read are binned in steps of 0.5, with an open-ended
+//! last bin, matching the thresholds usually given to `--max-ee`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Target number of histogram bins; the bin width is rounded up to 1, 2, 5, 10, 20, 50...
+const TARGET_BINS: usize = 50;
+
+/// GC histogram bins (0% to 100% in 1% steps)
+const GC_BINS: usize = 101;
+
+/// Reads longer than this are cut to a multiple of it before counting GC
+const GC_MODEL_MAX_LENGTH: usize = 1000;
+
+/// Half-width of the moving average applied before peak calling
+const GC_SMOOTHING_RADIUS: usize = 2;
+
+/// A secondary peak must reach this fraction of the highest peak
+const MIN_PEAK_HEIGHT: f64 = 0.15;
+
+/// Peaks closer than this (in GC percentage points) are treated as one
+const MIN_PEAK_SEPARATION: usize = 10;
+
+/// The valley between two peaks must drop below this fraction of the lower peak
+const MAX_VALLEY_DEPTH: f64 = 0.6;
+
+/// Width of an expected-errors histogram bin
+const EE_BIN_WIDTH: f64 = 0.5;
+
+/// Reads with at least this many expected errors share the last bin
+const EE_LAST_BIN_START: f64 = 20.0;
+
+/// Reads whose length falls in `[start, end]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LengthBin {
+    pub start: usize,
+    pub end: usize,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LengthDistribution {
+    pub reads: u64,
+    pub min_length: usize,
+    pub max_length: usize,
+    pub mean_length: f64,
+    pub n50: usize,
+    pub bin_width: usize,
+    pub histogram: Vec<LengthBin>,
+}
+
+/// Streaming read length counter
+#[derive(Debug, Clone, Default)]
+pub struct LengthDistributionAccumulator {
+    counts: BTreeMap<usize, u64>,
+}
+
+impl LengthDistributionAccumulator {
+    /// Count one read of the given length
+    pub fn add(&mut self, length: usize) {
+        *self.counts.entry(length).or_insert(0) += 1;
+    }
+
+    /// Fold in lengths from another set of reads
+    pub fn merge(&mut self, other: Self) {
+        for (length, count) in other.counts {
+            *self.counts.entry(length).or_insert(0) += count;
+        }
+    }
+
+    /// Summarise the lengths seen so far
+    pub fn finish(&self) -> LengthDistribution {
+        let reads: u64 = self.counts.values().sum();
+        if reads == 0 {
+            return LengthDistribution::default();
+        }
+
+        let min_length = *self.counts.keys().next().unwrap_or(&0);
+        let max_length = *self.counts.keys().next_back().unwrap_or(&0);
+        let total_bases: u64 = self.counts.iter().map(|(&length, &count)| length as u64 * count).sum();
+
+        // N50: the length at which reads of that length or longer hold half the bases
+        let mut n50 = 0;
+        let mut cumulative_bases = 0u64;
+        for (&length, &count) in self.counts.iter().rev() {
+            cumulative_bases += length as u64 * count;
+            if cumulative_bases * 2 >= total_bases {
+                n50 = length;
+                break;
+            }
+        }
+
+        let bin_width = bin_width(max_length - min_length + 1);
+        let first_bin = min_length / bin_width * bin_width;
+        let mut histogram: Vec<LengthBin> = (first_bin..=max_length)
+            .step_by(bin_width)
+            .map(|start| LengthBin {
+                start,
+                end: start + bin_width - 1,
+                count: 0,
+            })
+            .collect();
+        for (&length, &count) in &self.counts {
+            histogram[(length - first_bin) / bin_width].count += count;
+        }
+
+        LengthDistribution {
+            reads,
+            min_length,
+            max_length,
+            mean_length: total_bases as f64 / reads as f64,
+            n50,
+            bin_width,
+            histogram,
+        }
+    }
+}
+
+/// Smallest 1/2/5 × 10^n width that covers `span` lengths in about `TARGET_BINS` bins
+fn bin_width(span: usize) -> usize {
+    let mut magnitude = 1;
+    loop {
+        for step in [1, 2, 5] {
+            let width = step * magnitude;
+            if span.div_ceil(width) <= TARGET_BINS {
+                return width;
+            }
+        }
+        magnitude *= 10;
+    }
+}
+
+/// Reads whose expected errors fall in `[start, end)` (no end for the last bin)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpectedErrorsBin {
+    pub start: f64,
+    pub end: Option<f64>,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExpectedErrorsDistribution {
+    pub reads: u64,
+    pub mean_expected_errors: f64,
+    pub max_expected_errors: f64,
+    pub bin_width: f64,
+    pub histogram: Vec<ExpectedErrorsBin>, // From 0 up to the highest occupied bin
+}
+
+/// Streaming per-read expected-errors histogram
+#[derive(Debug, Clone, Default)]
+pub struct ExpectedErrorsAccumulator {
+    counts: BTreeMap<usize, u64>,
+    total: f64,
+    max: f64,
+}
+
+impl ExpectedErrorsAccumulator {
+    /// Count one read by its expected errors
+    pub fn add(&mut self, expected_errors: f64) {
+        let last_bin = (EE_LAST_BIN_START / EE_BIN_WIDTH) as usize;
+        let bin = ((expected_errors / EE_BIN_WIDTH) as usize).min(last_bin);
+        *self.counts.entry(bin).or_insert(0) += 1;
+        self.total += expected_errors;
+        self.max = self.max.max(expected_errors);
+    }
+
+    /// Fold in reads from another accumulator
+    pub fn merge(&mut self, other: Self) {
+        for (bin, count) in other.counts {
+            *self.counts.entry(bin).or_insert(0) += count;
+        }
+        self.total += other.total;
+        self.max = self.max.max(other.max);
+    }
+
+    /// Summarise the reads seen so far
+    pub fn finish(&self) -> ExpectedErrorsDistribution {
+        let reads: u64 = self.counts.values().sum();
+        let highest_bin = match self.counts.keys().next_back() {
+            Some(&bin) => bin,
+            None => return ExpectedErrorsDistribution::default(),
+        };
+
+        let histogram = (0..=highest_bin)
+            .map(|bin| {
+                let start = bin as f64 * EE_BIN_WIDTH;
+                ExpectedErrorsBin {
+                    start,
+                    end: (start < EE_LAST_BIN_START).then_some(start + EE_BIN_WIDTH),
+                    count: self.counts.get(&bin).copied().unwrap_or(0),
+                }
+            })
+            .collect();
+
+        ExpectedErrorsDistribution {
+            reads,
+            mean_expected_errors: self.total / reads as f64,
+            max_expected_errors: self.max,
+            bin_width: EE_BIN_WIDTH,
+            histogram,
+        }
+    }
+}
+
+/// Local maximum of the smoothed GC histogram
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GcPeak {
+    pub gc_percent: usize,
+    pub percent_of_reads: f64, // Smoothed height of the peak bin as a share of reads
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GcDistribution {
+    pub reads: u64,
+    pub mean_gc: f64,
+    pub mode_gc: f64,
+    pub std_dev: f64,
+    pub counts: Vec<f64>,      // Reads per GC percentage, index = GC%, spread as FastQC's GCModel
+    pub theoretical: Vec<f64>, // Normal curve around the mode scaled to the same reads
+    pub deviation_from_normal: f64, // % of reads outside the theoretical curve
+    pub peaks: Vec<GcPeak>,
+    pub multimodal: bool, // Two or more well separated peaks
+}
+
+impl Default for GcDistribution {
+    fn default() -> Self {
+        Self {
+            reads: 0,
+            mean_gc: 0.0,
+            mode_gc: 0.0,
+            std_dev: 0.0,
+            counts: vec![0.0; GC_BINS],
+            theoretical: vec![0.0; GC_BINS],
+            deviation_from_normal: 0.0,
+            peaks: Vec::new(),
+            multimodal: false,
+        }
+    }
+}
+
+/// Streaming per-read GC histogram
+///
+/// Reads are counted by (length, GC bases) and only spread over percentage
+/// bins in `finish`, so the result does not depend on how reads were batched.
+#[derive(Debug, Clone, Default)]
+pub struct GcDistributionAccumulator {
+    counts: BTreeMap<(usize, usize), u64>,
+}
+
+impl GcDistributionAccumulator {
+    /// Count one read by its G and C bases
+    pub fn add(&mut self, sequence: &[u8]) {
+        // Long reads are cut to a multiple of 1000bp, as FastQC does, to bound the number of models
+        let length = match sequence.len() {
+            length if length > GC_MODEL_MAX_LENGTH => length / GC_MODEL_MAX_LENGTH * GC_MODEL_MAX_LENGTH,
+            length => length,
+        };
+        if length == 0 {
+            return;
+        }
+
+        let gc = sequence[..length]
+            .iter()
+            .filter(|&&base| matches!(base.to_ascii_uppercase(), b'G' | b'C'))
+            .count();
+        *self.counts.entry((length, gc)).or_insert(0) += 1;
+    }
+
+    /// Fold in GC counts from another set of reads
+    pub fn merge(&mut self, other: Self) {
+        for (key, count) in other.counts {
+            *self.counts.entry(key).or_insert(0) += count;
+        }
+    }
+
+    /// Fit the normal curve and call peaks
+    pub fn finish(&self) -> GcDistribution {
+        let reads: u64 = self.counts.values().sum();
+        if reads == 0 {
+            return GcDistribution::default();
+        }
+
+        let mut counts = [0.0; GC_BINS];
+        let mut model: (usize, Vec<Vec<(usize, f64)>>) = (0, Vec::new());
+        for (&(length, gc), &count) in &self.counts {
+            if model.0 != length {
+                model = (length, gc_model(length));
+            }
+            for &(bin, weight) in &model.1[gc] {
+                counts[bin] += count as f64 * weight;
+            }
+        }
+        let total: f64 = counts.iter().sum();
+
+        let mean_gc = counts.iter().enumerate().map(|(gc, &count)| gc as f64 * count).sum::<f64>() / total;
+
+        // Centre the curve on the mode (as FastQC does) so a contaminant peak
+        // widens the fit instead of dragging it between the two populations
+        let smoothed = smooth(&counts);
+        let mode_gc = smoothed
+            .iter()
+            .enumerate()
+            .fold((0, f64::MIN), |best, (gc, &height)| if height > best.1 { (gc, height) } else { best })
+            .0 as f64;
+
+        let variance = counts
+            .iter()
+            .enumerate()
+            .map(|(gc, &count)| (gc as f64 - mode_gc).powi(2) * count)
+            .sum::<f64>()
+            / total;
+        let std_dev = variance.sqrt();
+
+        let theoretical: Vec<f64> = (0..GC_BINS)
+            .map(|gc| {
+                if std_dev == 0.0 {
+                    return if gc as f64 == mode_gc { total } else { 0.0 };
+                }
+                let z = (gc as f64 - mode_gc) / std_dev;
+                total * (-0.5 * z * z).exp() / (std_dev * (2.0 * std::f64::consts::PI).sqrt())
+            })
+            .collect();
+
+        let deviation: f64 = counts
+            .iter()
+            .zip(&theoretical)
+            .map(|(&observed, &expected)| (observed - expected).abs())
+            .sum();
+
+        let peaks: Vec<GcPeak> = find_peaks(&smoothed)
+            .into_iter()
+            .map(|gc| GcPeak {
+                gc_percent: gc,
+                percent_of_reads: smoothed[gc] / total * 100.0,
+            })
+            .collect();
+
+        GcDistribution {
+            reads,
+            mean_gc,
+            mode_gc,
+            std_dev,
+            counts: counts.to_vec(),
+            theoretical,
+            deviation_from_normal: deviation / total * 100.0,
+            multimodal: peaks.len() > 1,
+            peaks,
+        }
+    }
+}
+
+/// Percentage bins each GC count of a read of `length` bases can round into,
+/// with FastQC's GCModel weights: a bin claimed by `k` GC counts takes `1/k` of
+/// each, so every bin averages its claimants and no bin is left empty
+fn gc_model(length: usize) -> Vec<Vec<(usize, f64)>> {
+    // GC count g covers [g - 0.5, g + 0.5] bases and bin p covers [p - 0.5, p + 0.5)
+    // percent, compared in integer units of 1/(2 * length) percent
+    let claims: Vec<Vec<usize>> = (0..=length)
+        .map(|gc| {
+            let low = (2 * gc).saturating_sub(1) * 100;
+            let high = (2 * gc + 1).min(2 * length) * 100;
+            (0..GC_BINS)
+                .filter(|&bin| (2 * bin).saturating_sub(1) * length < high && (2 * bin + 1) * length > low)
+                .collect()
+        })
+        .collect();
+
+    let mut claimants = [0usize; GC_BINS];
+    for bins in &claims {
+        for &bin in bins {
+            claimants[bin] += 1;
+        }
+    }
+    claims
+        .into_iter()
+        .map(|bins| bins.into_iter().map(|bin| (bin, 1.0 / claimants[bin] as f64)).collect())
+        .collect()
+}
+
+/// Triangular moving average over `GC_SMOOTHING_RADIUS` bins either side
+/// (weights 1, 2, 3, 2, 1), so a narrow spike keeps a single maximum
+fn smooth(counts: &[f64; GC_BINS]) -> Vec<f64> {
+    (0..GC_BINS)
+        .map(|gc| {
+            let start = gc.saturating_sub(GC_SMOOTHING_RADIUS);
+            let end = (gc + GC_SMOOTHING_RADIUS).min(GC_BINS - 1);
+            let (weighted, weights) = (start..=end).fold((0.0, 0.0), |(sum, total), bin| {
+                let weight = (GC_SMOOTHING_RADIUS + 1 - gc.abs_diff(bin)) as f64;
+                (sum + counts[bin] * weight, total + weight)
+            });
+            weighted / weights
+        })
+        .collect()
+}
+
+/// Well separated local maxima of the smoothed histogram, in GC order
+fn find_peaks(smoothed: &[f64]) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..smoothed.len())
+        .filter(|&gc| {
+            let left = if gc > 0 { smoothed[gc - 1] } else { 0.0 };
+            let right = smoothed.get(gc + 1).copied().unwrap_or(0.0);
+            smoothed[gc] > 0.0 && smoothed[gc] >= left && smoothed[gc] > right
+        })
+        .collect();
+
+    // Highest first, so each candidate is compared against the stronger peaks
+    candidates.sort_by(|&a, &b| smoothed[b].total_cmp(&smoothed[a]).then(a.cmp(&b)));
+    let highest = match candidates.first() {
+        Some(&gc) => smoothed[gc],
+        None => return Vec::new(),
+    };
+
+    let mut peaks: Vec<usize> = Vec::new();
+    for gc in candidates {
+        if smoothed[gc] < highest * MIN_PEAK_HEIGHT {
+            break;
+        }
+
+        let separated = peaks.iter().all(|&peak| {
+            let (low, high) = (peak.min(gc), peak.max(gc));
+            let valley = smoothed[low..=high].iter().copied().fold(f64::MAX, f64::min);
+            high - low >= MIN_PEAK_SEPARATION && valley <= smoothed[gc].min(smoothed[peak]) * MAX_VALLEY_DEPTH
+        });
+        if separated {
+            peaks.push(gc);
+        }
+    }
+
+    peaks.sort_unstable();
+    peaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_summary_and_bins() {
+        let mut lengths = LengthDistributionAccumulator::default();
+        for length in [20, 30, 40, 150, 150] {
+            lengths.add(length);
+        }
+
+        let distribution = lengths.finish();
+        assert_eq!(distribution.reads, 5);
+        assert_eq!(distribution.min_length, 20);
+        assert_eq!(distribution.max_length, 150);
+        assert_eq!(distribution.n50, 150);
+        assert!((distribution.mean_length - 78.0).abs() < 1e-9);
+        assert_eq!(distribution.bin_width, 5);
+        assert_eq!(distribution.histogram.first().unwrap().start, 20);
+        assert_eq!(distribution.histogram.iter().map(|bin| bin.count).sum::<u64>(), 5);
+    }
+
+    #[test]
+    fn test_expected_errors_bins() {
+        let mut errors = ExpectedErrorsAccumulator::default();
+        for expected in [0.1, 0.4, 1.2, 35.0] {
+            errors.add(expected);
+        }
+
+        let distribution = errors.finish();
+        assert_eq!(distribution.reads, 4);
+        assert_eq!(distribution.max_expected_errors, 35.0);
+        assert_eq!(distribution.histogram.len(), 41);
+        assert_eq!(distribution.histogram[0].count, 2);
+        assert_eq!(distribution.histogram[2].count, 1);
+        assert_eq!(distribution.histogram[40].end, None);
+        assert_eq!(distribution.histogram[40].count, 1);
+    }
+
+    /// A read of `length` bases with `gc` G bases
+    fn gc_read(length: usize, gc: usize) -> Vec<u8> {
+        let mut read = vec![b'G'; gc];
+        read.resize(length, b'A');
+        read
+    }
+
+    #[test]
+    fn test_second_gc_peak_is_flagged() {
+        let mut gc = GcDistributionAccumulator::default();
+        for _ in 0..1000 {
+            gc.add(&gc_read(100, 42));
+        }
+        assert!(!gc.finish().multimodal);
+
+        for _ in 0..400 {
+            gc.add(&gc_read(100, 66));
+        }
+        let distribution = gc.finish();
+        assert!(distribution.multimodal);
+        assert_eq!(
+            distribution.peaks.iter().map(|peak| peak.gc_percent).collect::<Vec<_>>(),
+            vec![42, 66]
+        );
+    }
+
+    #[test]
+    fn test_binomial_gc_fits_normal_at_any_length() {
+        for length in [50, 75, 100, 150] {
+            // 100,000 reads with GC ~ Binomial(length, 0.5)
+            let mut gc = GcDistributionAccumulator::default();
+            let mut pmf = 0.5f64.powi(length as i32);
+            for gc_bases in 0..=length {
+                for _ in 0..(pmf * 100_000.0).round() as usize {
+                    gc.add(&gc_read(length, gc_bases));
+                }
+                pmf *= (length - gc_bases) as f64 / (gc_bases + 1) as f64;
+            }
+
+            let distribution = gc.finish();
+            assert!(distribution.counts[40..=60].iter().all(|&count| count > 0.0), "{}bp", length);
+            assert!(
+                distribution.deviation_from_normal < 15.0,
+                "{}bp: {:.1}% deviation",
+                length,
+                distribution.deviation_from_normal
+            );
+            assert!(!distribution.multimodal);
+        }
+    }
+}
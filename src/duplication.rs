@@ -0,0 +1,203 @@
+//! Sequence duplication level estimation
+//!
+//! FastQC-style "sequence duplication levels" from a bounded sample of read hashes:
+//! - The first 100,000 distinct sequences are tracked (see `counting`)
+//! - Later reads only increment tracked sequences
+//! - Counts are corrected for sequences that could not be seen before the limit
+//!
+//! The headline figure is the percentage of reads that would remain after
+//! exact-sequence deduplication, which tells whether running
+//! `biometal-pcr-dedup` is worthwhile.
+
+use crate::counting::BoundedCounter;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+/// Distinct sequences tracked before new ones are ignored
+pub const DEFAULT_MAX_TRACKED: usize = 100_000;
+
+/// Histogram bucket labels and their lowest duplication level
+const LEVEL_BUCKETS: [(&str, u64); 16] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+    (">10", 10),
+    (">50", 50),
+    (">100", 100),
+    (">500", 500),
+    (">1k", 1_000),
+    (">5k", 5_000),
+    (">10k", 10_000),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicationLevel {
+    pub level: String,
+    pub percent_of_deduplicated: f64, // Share of distinct sequences at this level
+    pub percent_of_total: f64,        // Share of all reads at this level
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DuplicationStats {
+    pub percent_remaining_if_deduplicated: f64,
+    pub reads_sampled: u64, // Reads seen before the tracking limit was reached
+    pub distinct_sequences_tracked: u64,
+    pub levels: Vec<DuplicationLevel>,
+}
+
+/// Bounded-memory duplication level estimator over exact-sequence hashes
+#[derive(Debug, Clone)]
+pub struct DuplicationEstimator {
+    counts: BoundedCounter<u64>,
+}
+
+impl Default for DuplicationEstimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_TRACKED)
+    }
+}
+
+impl DuplicationEstimator {
+    pub fn new(max_tracked: usize) -> Self {
+        Self {
+            counts: BoundedCounter::new(max_tracked),
+        }
+    }
+
+    /// Count one read by the hash of its full sequence
+    pub fn add(&mut self, sequence: &[u8]) {
+        self.counts.add(sequence_hash(sequence));
+    }
+
+    /// Fold in an estimator built over later reads
+    pub fn merge(&mut self, other: Self) {
+        self.counts.merge(other.counts);
+    }
+
+    /// Build the duplication histogram and deduplication estimate
+    pub fn finish(&self) -> DuplicationStats {
+        let total_reads = self.counts.observations();
+        let reads_at_limit = self.counts.observations_at_capacity();
+
+        // Number of distinct sequences seen at each duplication level
+        let mut sequences_per_level: BTreeMap<u64, u64> = BTreeMap::new();
+        for (_, tracked) in self.counts.iter() {
+            *sequences_per_level.entry(tracked.count).or_insert(0) += 1;
+        }
+
+        let mut deduplicated_total = 0.0;
+        let mut raw_total = 0.0;
+        let mut bucket_sequences = [0.0f64; LEVEL_BUCKETS.len()];
+        let mut bucket_reads = [0.0f64; LEVEL_BUCKETS.len()];
+
+        for (&level, &sequences) in &sequences_per_level {
+            let corrected = corrected_count(reads_at_limit, total_reads, level, sequences);
+            deduplicated_total += corrected;
+            raw_total += corrected * level as f64;
+
+            let bucket = LEVEL_BUCKETS
+                .iter()
+                .rposition(|&(_, floor)| level >= floor)
+                .unwrap_or(0);
+            bucket_sequences[bucket] += corrected;
+            bucket_reads[bucket] += corrected * level as f64;
+        }
+
+        let percentage = |value: f64, total: f64| if total > 0.0 { value / total * 100.0 } else { 0.0 };
+
+        let levels = LEVEL_BUCKETS
+            .iter()
+            .enumerate()
+            .map(|(i, &(label, _))| DuplicationLevel {
+                level: label.to_string(),
+                percent_of_deduplicated: percentage(bucket_sequences[i], deduplicated_total),
+                percent_of_total: percentage(bucket_reads[i], raw_total),
+            })
+            .collect();
+
+        DuplicationStats {
+            percent_remaining_if_deduplicated: percentage(deduplicated_total, raw_total),
+            reads_sampled: reads_at_limit,
+            distinct_sequences_tracked: self.counts.tracked() as u64,
+            levels,
+        }
+    }
+}
+
+/// Hash used to identify exact-duplicate sequences
+pub fn sequence_hash(sequence: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    sequence.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Correct the number of sequences observed at a duplication level for those
+/// whose first copy only appeared after the tracking limit (FastQC's correction)
+fn corrected_count(reads_at_limit: u64, total_reads: u64, level: u64, observed: u64) -> f64 {
+    // Every read was sampled, or the level is too high to have been missed
+    if reads_at_limit == total_reads || total_reads.saturating_sub(level) < reads_at_limit {
+        return observed as f64;
+    }
+
+    // Probability that none of the `level` copies fell within the sampled reads
+    let mut p_not_seen = 1.0;
+    let limit_of_caring = 1.0 - (observed as f64 / (observed as f64 + 0.01));
+    for i in 0..level {
+        let remaining = (total_reads - i) as f64;
+        p_not_seen *= (remaining - reads_at_limit as f64) / remaining;
+        if p_not_seen < limit_of_caring {
+            p_not_seen = 0.0;
+            break;
+        }
+    }
+
+    observed as f64 / (1.0 - p_not_seen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level<'a>(stats: &'a DuplicationStats, label: &str) -> &'a DuplicationLevel {
+        stats.levels.iter().find(|level| level.level == label).unwrap()
+    }
+
+    #[test]
+    fn test_levels_and_tracking_limit_correction() {
+        // Nothing untracked: 16 reads from 4 sequences seen 1, 2, 3 and 10 times
+        let mut estimator = DuplicationEstimator::default();
+        for (sequence, copies) in [(&b"AAAA"[..], 1), (b"CCCC", 2), (b"GGGG", 3), (b"TTTT", 10)] {
+            for _ in 0..copies {
+                estimator.add(sequence);
+            }
+        }
+        let stats = estimator.finish();
+        assert_eq!(stats.percent_remaining_if_deduplicated, 25.0);
+        assert_eq!(stats.levels.len(), LEVEL_BUCKETS.len());
+        assert_eq!(level(&stats, "2").percent_of_deduplicated, 25.0);
+        assert_eq!(level(&stats, "2").percent_of_total, 12.5);
+        assert_eq!(level(&stats, ">10").percent_of_total, 62.5);
+
+        // Tracking stops after 2 of 10 reads. X (4 copies) and Y (1 copy) are
+        // scaled up by the chance that no copy fell in the first 2 reads:
+        // Y by 1 / (1 - 8/10) = 5, X by 1 / (1 - (8*7*6*5)/(10*9*8*7)) = 1.5
+        let mut estimator = DuplicationEstimator::new(2);
+        for sequence in ["X", "Y", "X", "X", "X", "Z", "W", "Z", "W", "V"] {
+            estimator.add(sequence.as_bytes());
+        }
+        let stats = estimator.finish();
+        assert_eq!(stats.reads_sampled, 2);
+        assert_eq!(stats.distinct_sequences_tracked, 2);
+        assert!((stats.percent_remaining_if_deduplicated - 6.5 / 11.0 * 100.0).abs() < 1e-9);
+        assert!((level(&stats, "1").percent_of_deduplicated - 5.0 / 6.5 * 100.0).abs() < 1e-9);
+        assert!((level(&stats, "4").percent_of_total - 6.0 / 11.0 * 100.0).abs() < 1e-9);
+    }
+}
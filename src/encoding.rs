@@ -0,0 +1,186 @@
+//! Quality score encoding detection
+//!
+//! Samples the first records of a FASTQ file to choose the Phred offset:
+//! - Phred+33: Sanger, Illumina 1.8+ and SRA-normalised files
+//! - Phred+64: Illumina 1.3-1.7 (and Solexa-era files treated as Phred+64)
+//!
+//! Tools resolve the encoding once per input and convert raw quality bytes
+//! with the detected offset instead of assuming `q - 33`.
+
+use anyhow::Result;
+use biometal::io::{DataSource, FastqStream};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Default number of records sampled for encoding detection
+pub const DEFAULT_SAMPLE_SIZE: usize = 10_000;
+
+/// Lowest quality byte that only appears in Phred+33 data (';' is the Solexa+64 floor)
+const PHRED33_ONLY_BELOW: u8 = 59;
+
+/// Highest quality byte expected from Phred+33 data ('K' = Q42)
+const PHRED33_MAX: u8 = 75;
+
+/// FASTQ quality score encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum QualityEncoding {
+    #[default]
+    Phred33,
+    Phred64,
+}
+
+impl QualityEncoding {
+    /// ASCII offset subtracted from quality bytes
+    pub fn offset(self) -> u8 {
+        match self {
+            QualityEncoding::Phred33 => 33,
+            QualityEncoding::Phred64 => 64,
+        }
+    }
+
+    /// Convert a raw quality byte to a Phred score (clamped at zero)
+    pub fn phred(self, quality_byte: u8) -> u8 {
+        quality_byte.saturating_sub(self.offset())
+    }
+
+    /// Pick the encoding from the observed quality byte range
+    pub fn from_quality_range(min_byte: u8, max_byte: u8) -> Self {
+        if min_byte < PHRED33_ONLY_BELOW {
+            QualityEncoding::Phred33
+        } else if max_byte > PHRED33_MAX {
+            QualityEncoding::Phred64
+        } else {
+            // High-quality-only samples are ambiguous; modern data is Phred+33
+            QualityEncoding::Phred33
+        }
+    }
+}
+
+impl fmt::Display for QualityEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Phred+{}", self.offset())
+    }
+}
+
+impl FromStr for QualityEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "33" | "phred33" | "phred+33" | "sanger" => Ok(QualityEncoding::Phred33),
+            "64" | "phred64" | "phred+64" => Ok(QualityEncoding::Phred64),
+            other => Err(anyhow::anyhow!(
+                "Unknown quality encoding '{}' (expected auto, 33 or 64)",
+                other
+            )),
+        }
+    }
+}
+
+/// Encoding detector that samples the first records of a FASTQ file
+pub struct EncodingDetector {
+    pub sample_size: usize,
+}
+
+impl Default for EncodingDetector {
+    fn default() -> Self {
+        Self {
+            sample_size: DEFAULT_SAMPLE_SIZE,
+        }
+    }
+}
+
+impl EncodingDetector {
+    pub fn new(sample_size: usize) -> Self {
+        Self { sample_size }
+    }
+
+    /// Detect the quality encoding from the first `sample_size` records
+    pub fn detect<P: AsRef<Path>>(&self, fastq_path: P) -> Result<QualityEncoding> {
+        let data_source = DataSource::from_path(&fastq_path);
+        let fastq_stream = FastqStream::new(data_source)?;
+
+        let mut min_byte = u8::MAX;
+        let mut max_byte = u8::MIN;
+
+        for record_result in fastq_stream.take(self.sample_size) {
+            let record = record_result?;
+            for &q in &record.quality {
+                min_byte = min_byte.min(q);
+                max_byte = max_byte.max(q);
+            }
+        }
+
+        if min_byte > max_byte {
+            // No quality bytes sampled (empty file)
+            return Ok(QualityEncoding::default());
+        }
+
+        Ok(QualityEncoding::from_quality_range(min_byte, max_byte))
+    }
+}
+
+/// Resolve a `--phred-offset` argument: `auto` samples the input, otherwise parse the override
+pub fn resolve_encoding<P: AsRef<Path>>(fastq_path: P, phred_offset_arg: &str) -> Result<QualityEncoding> {
+    if phred_offset_arg.eq_ignore_ascii_case("auto") {
+        EncodingDetector::default().detect(fastq_path)
+    } else {
+        phred_offset_arg.parse()
+    }
+}
+
+/// Mean Phred score of a quality string using the given encoding
+pub fn mean_phred(quality: &[u8], encoding: QualityEncoding) -> f64 {
+    if quality.is_empty() {
+        return 0.0;
+    }
+
+    let sum: u64 = quality.iter().map(|&q| encoding.phred(q) as u64).sum();
+    sum as f64 / quality.len() as f64
+}
+
+/// Expected number of errors in a read: the sum of per-base error
+/// probabilities `10^(-Q/10)` (USEARCH / vsearch / DADA2 `maxEE`)
+pub fn expected_errors(quality: &[u8], encoding: QualityEncoding) -> f64 {
+    quality
+        .iter()
+        .map(|&q| 10f64.powf(-(encoding.phred(q) as f64) / 10.0))
+        .sum()
+}
+
+/// Serde default for stats files written before the offset was recorded
+pub(crate) fn default_phred_offset() -> u8 {
+    QualityEncoding::Phred33.offset()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_phred33_from_low_scores() {
+        // '#' (Q2) only exists in Phred+33
+        assert_eq!(QualityEncoding::from_quality_range(b'#', b'J'), QualityEncoding::Phred33);
+    }
+
+    #[test]
+    fn test_detects_phred64_from_high_scores() {
+        // Illumina 1.5: 'B' floor, 'h' (Q40) ceiling
+        assert_eq!(QualityEncoding::from_quality_range(b'B', b'h'), QualityEncoding::Phred64);
+    }
+
+    #[test]
+    fn test_mean_phred_does_not_underflow() {
+        assert_eq!(mean_phred(b"!!", QualityEncoding::Phred64), 0.0);
+        assert_eq!(mean_phred(b"hh", QualityEncoding::Phred64), 40.0);
+    }
+
+    #[test]
+    fn test_expected_errors_sums_error_probabilities() {
+        // Q10 + Q20 + Q30 = 0.1 + 0.01 + 0.001; Q0 is a certain error
+        assert!((expected_errors(b"+5?", QualityEncoding::Phred33) - 0.111).abs() < 1e-12);
+        assert_eq!(expected_errors(b"!", QualityEncoding::Phred33), 1.0);
+    }
+}
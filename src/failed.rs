@@ -0,0 +1,75 @@
+//! Rejected-read output (`--failed-out`)
+//!
+//! Reads a tool discards are written as FASTQ, unchanged except for
+//! `reason=<tag>` appended to the header comment, so they can be audited and
+//! false positives rescued. Tags are the snake_case names used for
+//! `reads_failed_by_reason` in the quality filter statistics.
+
+use crate::trimming::FailureReason;
+use anyhow::Result;
+use biometal::FastqRecord;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// FASTQ writer for discarded reads
+pub struct FailedReadWriter<W: Write = BufWriter<File>> {
+    writer: W,
+    reads_written: u64,
+}
+
+impl FailedReadWriter {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> FailedReadWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            reads_written: 0,
+        }
+    }
+
+    /// Write a discarded read with its rejection reason
+    pub fn write_record(&mut self, record: &FastqRecord, reason: FailureReason) -> Result<()> {
+        writeln!(self.writer, "@{} reason={}", record.id, reason.tag())?;
+        writeln!(self.writer, "{}", String::from_utf8_lossy(&record.sequence))?;
+        writeln!(self.writer, "+")?;
+        writeln!(self.writer, "{}", String::from_utf8_lossy(&record.quality))?;
+        self.reads_written += 1;
+        Ok(())
+    }
+
+    pub fn reads_written(&self) -> u64 {
+        self.reads_written
+    }
+
+    /// Flush and hand back the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reason_is_appended_to_header() {
+        let mut failed = FailedReadWriter::new(Vec::new());
+        let record = FastqRecord::new("read1 1:N:0:ACGT".to_string(), b"ACGT".to_vec(), b"IIII".to_vec());
+        failed.write_record(&record, FailureReason::LowMeanQuality).unwrap();
+        assert_eq!(failed.reads_written(), 1);
+
+        let written = String::from_utf8(failed.finish().unwrap()).unwrap();
+        assert_eq!(written, "@read1 1:N:0:ACGT reason=low_mean_quality\nACGT\n+\nIIII\n");
+
+        // Tags match the statistics keys
+        for reason in [FailureReason::TooShort, FailureReason::TooManyExpectedErrors, FailureReason::HostMatch] {
+            assert_eq!(serde_json::to_value(reason).unwrap(), reason.tag());
+        }
+    }
+}
@@ -0,0 +1,767 @@
+//! Self-contained HTML reports
+//!
+//! Renders `QcStats` as a single HTML file with inline CSS and inline SVG
+//! charts, so reports open on offline machines without any external assets.
+//! `QcComparison` gets the same treatment with before/after overlays.
+
+use crate::compare::{MetricDelta, PositionQualityDelta, QcComparison};
+use crate::distributions::LengthDistribution;
+use crate::verdicts::Verdict;
+use crate::QcStats;
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::path::Path;
+
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 300.0;
+const MARGIN_LEFT: f64 = 60.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 45.0;
+
+const STYLE: &str = "body{font-family:Helvetica,Arial,sans-serif;margin:2em auto;max-width:860px;color:#222}\
+h1{font-size:1.6em}h2{font-size:1.2em;margin-top:2em;border-bottom:1px solid #ccc}\
+table{border-collapse:collapse;margin:0.5em 0}td,th{padding:3px 10px;border:1px solid #ddd;text-align:left}\
+th{background:#f3f3f3}.seq{font-family:monospace}.warn{color:#b35900;font-weight:bold}\
+.pass{color:#2e7d32;font-weight:bold}.fail{color:#c62828;font-weight:bold}\
+.legend span{margin-right:1em}svg text{font-size:11px}";
+
+/// One line of a line chart
+pub struct Series {
+    pub name: String,
+    pub color: &'static str,
+    pub points: Vec<(f64, f64)>,
+    pub dashed: bool,
+}
+
+/// Write the quality-stats report to `path`
+pub fn write_quality_report<P: AsRef<Path>>(stats: &QcStats, path: P) -> Result<()> {
+    std::fs::write(path, render_quality_report(stats))?;
+    Ok(())
+}
+
+/// Render the quality-stats report as a complete HTML document
+pub fn render_quality_report(stats: &QcStats) -> String {
+    let mut body = String::new();
+
+    let _ = write!(body, "<h1>Quality report: {}</h1>", escape(&stats.sample_name));
+    body.push_str(&summary_table(stats));
+
+    body.push_str("<h2>Module verdicts</h2>");
+    body.push_str(&verdict_table(stats));
+
+    body.push_str("<h2>Per-position quality</h2>");
+    body.push_str(&position_quality_chart(stats));
+
+    body.push_str("<h2>Per-position base composition</h2>");
+    body.push_str(&composition_chart(stats));
+
+    body.push_str("<h2>Read length distribution</h2>");
+    body.push_str(&length_chart(stats));
+
+    body.push_str("<h2>GC content distribution</h2>");
+    body.push_str(&gc_chart(stats));
+
+    body.push_str("<h2>Sequence duplication levels</h2>");
+    body.push_str(&duplication_chart(stats));
+
+    body.push_str("<h2>Overrepresented sequences</h2>");
+    body.push_str(&overrepresented_table(stats));
+
+    body.push_str("<h2>Adapter content</h2>");
+    body.push_str(&adapter_chart(stats));
+
+    body.push_str("<h2>Per-tile quality</h2>");
+    body.push_str(&tile_table(stats));
+
+    document(&format!("{} - biometal quality report", stats.sample_name), &body)
+}
+
+/// Write the before/after comparison report to `path`
+pub fn write_comparison_report<P: AsRef<Path>>(comparison: &QcComparison, path: P) -> Result<()> {
+    std::fs::write(path, render_comparison_report(comparison))?;
+    Ok(())
+}
+
+/// Render a before/after comparison as a complete HTML document
+pub fn render_comparison_report(comparison: &QcComparison) -> String {
+    let mut body = String::new();
+
+    let _ = write!(
+        body,
+        "<h1>Comparison: {} &rarr; {}</h1>",
+        escape(&comparison.before_sample),
+        escape(&comparison.after_sample)
+    );
+    body.push_str(&comparison_table(comparison));
+
+    body.push_str("<h2>Per-position quality</h2>");
+    body.push_str(&comparison_quality_chart(comparison));
+
+    body.push_str("<h2>Read length distribution</h2>");
+    body.push_str(&comparison_length_chart(comparison));
+
+    document(
+        &format!("{} vs {} - biometal comparison", comparison.before_sample, comparison.after_sample),
+        &body,
+    )
+}
+
+fn document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <title>{}</title><style>{}</style></head>\
+         <body>{}</body></html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+fn comparison_table(comparison: &QcComparison) -> String {
+    let rows = [
+        (
+            "Reads",
+            comparison.reads_before.to_string(),
+            comparison.reads_after.to_string(),
+            format!("{:.2}% retained", comparison.read_retention),
+        ),
+        (
+            "Bases",
+            comparison.bases_before.to_string(),
+            comparison.bases_after.to_string(),
+            format!("{:.2}% retained", comparison.base_retention),
+        ),
+        delta_row("Mean quality", &comparison.mean_quality, ""),
+        delta_row("Q30 bases", &comparison.q30_bases, "%"),
+        delta_row("GC content", &comparison.gc_content, "%"),
+        delta_row("Mean length", &comparison.mean_length, " bp"),
+        delta_row("N50", &comparison.n50, " bp"),
+    ];
+
+    let mut html = String::from("<table><tr><th></th><th>Before</th><th>After</th><th>Change</th></tr>");
+    for (label, before, after, change) in rows {
+        let _ = write!(
+            html,
+            "<tr><th>{}</th><td>{}</td><td>{}</td><td>{}</td></tr>",
+            label, before, after, change
+        );
+    }
+    html.push_str("</table>");
+    html
+}
+
+fn delta_row(label: &'static str, metric: &MetricDelta, unit: &str) -> (&'static str, String, String, String) {
+    (
+        label,
+        format!("{:.2}{}", metric.before, unit),
+        format!("{:.2}{}", metric.after, unit),
+        format!("{:+.2}{}", metric.delta, unit),
+    )
+}
+
+fn comparison_quality_chart(comparison: &QcComparison) -> String {
+    let positions = &comparison.per_position_quality;
+    let series_of = |name: &str, color: &'static str, dashed: bool, value: fn(&PositionQualityDelta) -> Option<f64>| Series {
+        name: name.to_string(),
+        color,
+        points: positions
+            .iter()
+            .filter_map(|p| value(p).map(|v| (p.position as f64, v)))
+            .collect(),
+        dashed,
+    };
+
+    let series = [
+        series_of("Mean before", "#999999", false, |p| p.mean_before),
+        series_of("Mean after", "#1f4e9c", false, |p| p.mean_after),
+        series_of("Median before", "#999999", true, |p| p.median_before),
+        series_of("Median after", "#1f4e9c", true, |p| p.median_after),
+    ];
+
+    line_chart(&series, "Position in read (bp)", "Phred quality", None)
+}
+
+fn comparison_length_chart(comparison: &QcComparison) -> String {
+    // Normalise by bin width so histograms with different bins share an axis
+    let series_of = |name: &str, color: &'static str, distribution: &LengthDistribution| Series {
+        name: name.to_string(),
+        color,
+        points: distribution
+            .histogram
+            .iter()
+            .map(|bin| {
+                let share = bin.count as f64 / distribution.reads.max(1) as f64 * 100.0;
+                (bin.start as f64, share / distribution.bin_width.max(1) as f64)
+            })
+            .collect(),
+        dashed: false,
+    };
+
+    let series = [
+        series_of("Before", "#999999", &comparison.length_before),
+        series_of("After", "#1f4e9c", &comparison.length_after),
+    ];
+
+    line_chart(&series, "Read length (bp)", "% of reads per bp", None)
+}
+
+fn summary_table(stats: &QcStats) -> String {
+    let rows = [
+        ("Total reads", stats.total_reads.to_string()),
+        ("Total bases", stats.total_bases.to_string()),
+        ("Quality encoding", format!("Phred+{}", stats.phred_offset)),
+        ("GC content", format!("{:.2}%", stats.gc_content)),
+        ("Mean quality", format!("{:.2}", stats.mean_quality)),
+        ("Q30 bases", format!("{:.2}%", stats.q30_bases)),
+        ("Complexity", format!("{:.3}", stats.complexity_score)),
+        (
+            "Read length",
+            format!(
+                "{}-{} (mean {:.1}, N50 {})",
+                stats.length_distribution.min_length,
+                stats.length_distribution.max_length,
+                stats.length_distribution.mean_length,
+                stats.length_distribution.n50
+            ),
+        ),
+        (
+            "Reads remaining if deduplicated",
+            format!("{:.2}%", stats.duplication.percent_remaining_if_deduplicated),
+        ),
+    ];
+
+    let mut html = String::from("<table>");
+    for (label, value) in rows {
+        let _ = write!(html, "<tr><th>{}</th><td>{}</td></tr>", label, value);
+    }
+    html.push_str("</table>");
+    html
+}
+
+fn position_quality_chart(stats: &QcStats) -> String {
+    let positions = &stats.per_position_quality;
+    if positions.is_empty() {
+        return no_data();
+    }
+
+    let x_max = positions.len() as f64 + 1.0;
+    let y_max = positions
+        .iter()
+        .map(|p| p.percentile_90)
+        .fold(40.0, f64::max)
+        .ceil();
+    let plot = PlotArea::new(0.0, x_max, 0.0, y_max);
+
+    // Background bands: good (>= 28), reasonable (20-28), poor (< 20)
+    let mut svg = String::new();
+    for (low, high, color) in [(28.0, y_max, "#e6f4e6"), (20.0, 28.0, "#fbf1dc"), (0.0, 20.0, "#f8e1e1")] {
+        let _ = write!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+            plot.x(0.0),
+            plot.y(high),
+            plot.x(x_max) - plot.x(0.0),
+            plot.y(low) - plot.y(high),
+            color
+        );
+    }
+
+    // Box per cycle: 10th-90th whiskers and the interquartile box
+    let box_width = ((plot.x(1.0) - plot.x(0.0)) * 0.6).max(1.0);
+    for p in positions {
+        let x = plot.x(p.position as f64);
+        let _ = write!(
+            svg,
+            "<line x1=\"{x:.1}\" y1=\"{:.1}\" x2=\"{x:.1}\" y2=\"{:.1}\" stroke=\"#555\"/>\
+             <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#f2e394\" stroke=\"#555\" stroke-width=\"0.5\"/>",
+            plot.y(p.percentile_10),
+            plot.y(p.percentile_90),
+            x - box_width / 2.0,
+            plot.y(p.upper_quartile),
+            box_width,
+            (plot.y(p.lower_quartile) - plot.y(p.upper_quartile)).max(0.5),
+        );
+    }
+
+    let series = [
+        Series {
+            name: "Median".to_string(),
+            color: "#c0392b",
+            points: positions.iter().map(|p| (p.position as f64, p.median)).collect(),
+            dashed: false,
+        },
+        Series {
+            name: "Mean".to_string(),
+            color: "#1f4e9c",
+            points: positions.iter().map(|p| (p.position as f64, p.mean)).collect(),
+            dashed: false,
+        },
+    ];
+    svg.push_str(&plot.polylines(&series));
+
+    plot.wrap(&svg, "Position in read (bp)", "Phred score", &series)
+}
+
+fn composition_chart(stats: &QcStats) -> String {
+    let positions = &stats.per_position_composition;
+    if positions.is_empty() {
+        return no_data();
+    }
+
+    let series: Vec<Series> = [
+        ("A", "#2e8b57"),
+        ("C", "#1f4e9c"),
+        ("G", "#333333"),
+        ("T", "#c0392b"),
+        ("N", "#b35900"),
+    ]
+    .into_iter()
+    .map(|(base, color)| Series {
+        name: base.to_string(),
+        color,
+        points: positions
+            .iter()
+            .map(|p| {
+                let value = match base {
+                    "A" => p.a,
+                    "C" => p.c,
+                    "G" => p.g,
+                    "T" => p.t,
+                    _ => p.n,
+                };
+                (p.position as f64, value)
+            })
+            .collect(),
+        dashed: base == "N",
+    })
+    .collect();
+
+    line_chart(&series, "Position in read (bp)", "% of bases", Some(100.0))
+}
+
+fn adapter_chart(stats: &QcStats) -> String {
+    if stats.adapter_content.is_empty() {
+        return no_data();
+    }
+
+    const COLORS: [&str; 4] = ["#c0392b", "#1f4e9c", "#2e8b57", "#b35900"];
+    let series: Vec<Series> = stats
+        .adapter_content
+        .iter()
+        .enumerate()
+        .map(|(index, content)| Series {
+            name: content.adapter.clone(),
+            color: COLORS[index % COLORS.len()],
+            points: content
+                .percent_by_position
+                .iter()
+                .enumerate()
+                .map(|(position, &percent)| (position as f64 + 1.0, percent))
+                .collect(),
+            dashed: false,
+        })
+        .collect();
+
+    line_chart(&series, "Position in read (bp)", "% of reads", Some(100.0))
+}
+
+fn length_chart(stats: &QcStats) -> String {
+    let histogram = &stats.length_distribution.histogram;
+    if histogram.is_empty() {
+        return no_data();
+    }
+
+    let labels: Vec<String> = histogram
+        .iter()
+        .map(|bin| {
+            if bin.start == bin.end {
+                bin.start.to_string()
+            } else {
+                format!("{}-{}", bin.start, bin.end)
+            }
+        })
+        .collect();
+    let values: Vec<f64> = histogram.iter().map(|bin| bin.count as f64).collect();
+
+    bar_chart(&labels, &values, "Read length (bp)", "Reads")
+}
+
+fn gc_chart(stats: &QcStats) -> String {
+    let gc = &stats.gc_distribution;
+    if gc.reads == 0 {
+        return no_data();
+    }
+
+    let series = [
+        Series {
+            name: "Observed".to_string(),
+            color: "#c0392b",
+            points: gc.counts.iter().enumerate().map(|(i, &c)| (i as f64, c)).collect(),
+            dashed: false,
+        },
+        Series {
+            name: "Theoretical (normal)".to_string(),
+            color: "#1f4e9c",
+            points: gc.theoretical.iter().enumerate().map(|(i, &c)| (i as f64, c)).collect(),
+            dashed: true,
+        },
+    ];
+
+    let mut html = line_chart(&series, "Mean GC content (%)", "Reads", None);
+    let _ = write!(
+        html,
+        "<p>Mode {:.0}% GC, {:.1}% of reads deviate from the normal fit.</p>",
+        gc.mode_gc, gc.deviation_from_normal
+    );
+    if gc.multimodal {
+        let peaks: Vec<String> = gc.peaks.iter().map(|p| format!("{}%", p.gc_percent)).collect();
+        let _ = write!(
+            html,
+            "<p class=\"warn\">Multimodal GC profile (peaks at {}): possible bacterial or host contamination.</p>",
+            peaks.join(", ")
+        );
+    }
+    html
+}
+
+fn duplication_chart(stats: &QcStats) -> String {
+    let levels = &stats.duplication.levels;
+    if levels.is_empty() {
+        return no_data();
+    }
+
+    let labels: Vec<String> = levels.iter().map(|l| l.level.clone()).collect();
+    let values: Vec<f64> = levels.iter().map(|l| l.percent_of_total).collect();
+
+    let mut html = bar_chart(&labels, &values, "Sequence duplication level", "% of reads");
+    let _ = write!(
+        html,
+        "<p>{:.2}% of reads would remain after deduplication.</p>",
+        stats.duplication.percent_remaining_if_deduplicated
+    );
+    html
+}
+
+fn verdict_table(stats: &QcStats) -> String {
+    if stats.verdicts.is_empty() {
+        return no_data();
+    }
+
+    let mut html = String::from("<table><tr><th>Module</th><th>Verdict</th><th>Detail</th></tr>");
+    for verdict in &stats.verdicts {
+        let class = match verdict.verdict {
+            Verdict::Pass => "pass",
+            Verdict::Warn => "warn",
+            Verdict::Fail => "fail",
+        };
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td class=\"{}\">{}</td><td>{}</td></tr>",
+            verdict.module.title(),
+            class,
+            verdict.verdict,
+            escape(&verdict.detail)
+        );
+    }
+    html.push_str("</table>");
+    html
+}
+
+fn overrepresented_table(stats: &QcStats) -> String {
+    if stats.overrepresented_sequences.is_empty() {
+        return "<p>No overrepresented sequences.</p>".to_string();
+    }
+
+    let mut html = String::from("<table><tr><th>Sequence</th><th>Count</th><th>%</th><th>Possible source</th></tr>");
+    for hit in &stats.overrepresented_sequences {
+        let _ = write!(
+            html,
+            "<tr><td class=\"seq\">{}</td><td>{}</td><td>{:.2}</td><td>{}</td></tr>",
+            escape(&hit.sequence),
+            hit.count,
+            hit.percentage,
+            escape(&hit.possible_source)
+        );
+    }
+    html.push_str("</table>");
+    html
+}
+
+fn tile_table(stats: &QcStats) -> String {
+    let report = &stats.tile_quality;
+    if report.tiles.is_empty() {
+        return "<p>No Illumina tile coordinates found in read headers.</p>".to_string();
+    }
+
+    let mut html = String::from(
+        "<table><tr><th>Lane</th><th>Tile</th><th>Reads</th><th>Mean quality</th>\
+         <th>vs lane median</th><th>Worst cycle</th></tr>",
+    );
+    for tile in &report.tiles {
+        let _ = write!(
+            html,
+            "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{:.2}</td><td>{:+.2}</td><td>{} ({:+.2})</td></tr>",
+            if tile.flagged { " class=\"warn\"" } else { "" },
+            tile.lane,
+            tile.tile,
+            tile.reads,
+            tile.mean_quality,
+            tile.deviation_from_lane_median,
+            tile.worst_cycle,
+            tile.worst_cycle_deviation
+        );
+    }
+    html.push_str("</table>");
+    html
+}
+
+/// Line chart with an automatic x range and optional fixed y maximum
+pub fn line_chart(series: &[Series], x_label: &str, y_label: &str, y_max: Option<f64>) -> String {
+    let points = series.iter().flat_map(|s| s.points.iter());
+    let (x_min, x_max, data_y_max) = points.fold((f64::MAX, f64::MIN, 0.0f64), |(lo, hi, top), &(x, y)| {
+        (lo.min(x), hi.max(x), top.max(y))
+    });
+    if x_min > x_max {
+        return no_data();
+    }
+
+    let plot = PlotArea::new(x_min, x_max.max(x_min + 1.0), 0.0, y_max.unwrap_or(data_y_max).max(1.0));
+    plot.wrap(&plot.polylines(series), x_label, y_label, series)
+}
+
+/// Vertical bar chart with one labelled bar per value
+pub fn bar_chart(labels: &[String], values: &[f64], x_label: &str, y_label: &str) -> String {
+    let y_max = values.iter().copied().fold(0.0, f64::max).max(1.0);
+    let plot = PlotArea::new(0.0, values.len() as f64, 0.0, y_max);
+    let slot = plot.x(1.0) - plot.x(0.0);
+    let label_every = (values.len() / 15).max(1);
+
+    let mut svg = String::new();
+    for (i, (&value, label)) in values.iter().zip(labels).enumerate() {
+        let _ = write!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#1f4e9c\"><title>{}: {}</title></rect>",
+            plot.x(i as f64) + slot * 0.1,
+            plot.y(value),
+            slot * 0.8,
+            plot.y(0.0) - plot.y(value),
+            escape(label),
+            format_number(value)
+        );
+        if i % label_every == 0 {
+            let _ = write!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+                plot.x(i as f64 + 0.5),
+                plot.y(0.0) + 14.0,
+                escape(label)
+            );
+        }
+    }
+
+    plot.wrap_with_x_ticks(&svg, x_label, y_label, &[], false)
+}
+
+/// Maps data coordinates onto the SVG drawing area
+struct PlotArea {
+    x_min: f64,
+    x_max: f64,
+    y_min: f64,
+    y_max: f64,
+}
+
+impl PlotArea {
+    fn new(x_min: f64, x_max: f64, y_min: f64, y_max: f64) -> Self {
+        Self { x_min, x_max, y_min, y_max }
+    }
+
+    fn x(&self, value: f64) -> f64 {
+        let width = CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+        MARGIN_LEFT + (value - self.x_min) / (self.x_max - self.x_min) * width
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        let height = CHART_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+        MARGIN_TOP + height - (value.clamp(self.y_min, self.y_max) - self.y_min) / (self.y_max - self.y_min) * height
+    }
+
+    fn polylines(&self, series: &[Series]) -> String {
+        let mut svg = String::new();
+        for s in series {
+            let points: Vec<String> = s
+                .points
+                .iter()
+                .map(|&(x, y)| format!("{:.1},{:.1}", self.x(x), self.y(y)))
+                .collect();
+            let _ = write!(
+                svg,
+                "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\"{} points=\"{}\"/>",
+                s.color,
+                if s.dashed { " stroke-dasharray=\"5,3\"" } else { "" },
+                points.join(" ")
+            );
+        }
+        svg
+    }
+
+    fn wrap(&self, content: &str, x_label: &str, y_label: &str, series: &[Series]) -> String {
+        self.wrap_with_x_ticks(content, x_label, y_label, series, true)
+    }
+
+    /// Axes, ticks, labels and legend around already-drawn content
+    fn wrap_with_x_ticks(&self, content: &str, x_label: &str, y_label: &str, series: &[Series], x_ticks: bool) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = CHART_WIDTH,
+            h = CHART_HEIGHT
+        );
+        svg.push_str(content);
+
+        let (left, right) = (self.x(self.x_min), self.x(self.x_max));
+        let (top, bottom) = (self.y(self.y_max), self.y(self.y_min));
+        let _ = write!(
+            svg,
+            "<line x1=\"{left:.1}\" y1=\"{bottom:.1}\" x2=\"{right:.1}\" y2=\"{bottom:.1}\" stroke=\"#000\"/>\
+             <line x1=\"{left:.1}\" y1=\"{top:.1}\" x2=\"{left:.1}\" y2=\"{bottom:.1}\" stroke=\"#000\"/>"
+        );
+
+        for value in ticks(self.y_min, self.y_max) {
+            let y = self.y(value);
+            let _ = write!(
+                svg,
+                "<line x1=\"{:.1}\" y1=\"{y:.1}\" x2=\"{left:.1}\" y2=\"{y:.1}\" stroke=\"#000\"/>\
+                 <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+                left - 4.0,
+                left - 6.0,
+                y + 4.0,
+                format_number(value)
+            );
+        }
+
+        if x_ticks {
+            for value in ticks(self.x_min, self.x_max) {
+                let x = self.x(value);
+                let _ = write!(
+                    svg,
+                    "<line x1=\"{x:.1}\" y1=\"{bottom:.1}\" x2=\"{x:.1}\" y2=\"{:.1}\" stroke=\"#000\"/>\
+                     <text x=\"{x:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+                    bottom + 4.0,
+                    bottom + 16.0,
+                    format_number(value)
+                );
+            }
+        }
+
+        let _ = write!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\
+             <text transform=\"translate(14,{:.1}) rotate(-90)\" text-anchor=\"middle\">{}</text></svg>",
+            (left + right) / 2.0,
+            CHART_HEIGHT - 8.0,
+            escape(x_label),
+            (top + bottom) / 2.0,
+            escape(y_label)
+        );
+
+        if !series.is_empty() {
+            svg.push_str("<div class=\"legend\">");
+            for s in series {
+                let _ = write!(svg, "<span style=\"color:{}\">&#9632; {}</span>", s.color, escape(&s.name));
+            }
+            svg.push_str("</div>");
+        }
+        svg
+    }
+}
+
+/// Round tick positions (1, 2 or 5 × 10^n apart) covering `[min, max]`
+fn ticks(min: f64, max: f64) -> Vec<f64> {
+    let span = max - min;
+    if span <= 0.0 {
+        return vec![min];
+    }
+
+    let magnitude = 10f64.powf((span / 5.0).log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|step| span / step <= 6.0)
+        .unwrap_or(10.0 * magnitude);
+
+    let mut values = Vec::new();
+    let mut value = (min / step).ceil() * step;
+    while value <= max + step * 1e-9 {
+        values.push(value);
+        value += step;
+    }
+    values
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract().abs() < 1e-9 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+fn no_data() -> String {
+    "<p>No data.</p>".to_string()
+}
+
+/// Escape text for HTML element content and attribute values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::QualityEncoding;
+    use crate::quality::QualityAccumulator;
+    use crate::verdicts::{QcLimits, QcModule};
+    use biometal::FastqRecord;
+
+    #[test]
+    fn test_quality_report_sections_and_escaping() {
+        let mut accumulator = QualityAccumulator::new("S<1> & \"raw\"", QualityEncoding::Phred33, 1);
+        for sequence in [&b"ACGTACGTACGTNACGTACG"[..], b"GGGGCCCCAAAATTTTACGT", b"ACGTACGTACGTACGTAC"] {
+            accumulator.add(&FastqRecord::new("r".to_string(), sequence.to_vec(), vec![b'I'; sequence.len()]));
+        }
+        let mut stats = accumulator.finish();
+        stats.verdicts = QcLimits::default().evaluate(&stats);
+
+        let html = render_quality_report(&stats);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>S&lt;1&gt; &amp; &quot;raw&quot;"));
+        assert!(html.contains("<h1>Quality report: S&lt;1&gt; &amp; &quot;raw&quot;</h1>"));
+        assert!(!html.contains("S<1>"));
+        for section in [
+            "Module verdicts",
+            "Per-position quality",
+            "Per-position base composition",
+            "Read length distribution",
+            "GC content distribution",
+            "Sequence duplication levels",
+            "Overrepresented sequences",
+            "Adapter content",
+            "Per-tile quality",
+        ] {
+            assert!(html.contains(&format!("<h2>{}</h2>", section)), "{}", section);
+        }
+        assert!(html.contains("<svg"));
+        assert!(html.contains(QcModule::GcContent.title()));
+    }
+}
@@ -4,21 +4,61 @@
 //!
 //! This library provides shared functionality for:
 //! - Quality statistics and base composition analysis
+//! - Quality score encoding detection
+//! - Overrepresented sequence detection
+//! - Sequence duplication level estimation
+//! - Library complexity and saturation curve estimation
+//! - Read length and GC content distributions
+//! - Per-tile quality analysis from Illumina read headers
+//! - FastQC-style PASS/WARN/FAIL module verdicts
 //! - Contamination screening
 //! - VLP assessment metrics
 //! - Multi-sample QC reporting
+//! - Before/after comparison of processing steps
+//! - Trimmomatic-style quality trimming
+//! - Low-complexity filtering and DUST masking
+//! - Rejected-read output with reason tags
+//! - PolyX tail trimming with mismatch tolerance
+//! - Multithreaded, order-preserving record processing
+//! - Self-contained HTML reports
+//! - MultiQC custom content output
 
 pub mod quality;
+pub mod encoding;
+pub mod counting;
+pub mod overrepresented;
+pub mod duplication;
+pub mod saturation;
+pub mod distributions;
+pub mod tiles;
+pub mod verdicts;
 pub mod contamination;
 pub mod vlp;
 pub mod reporting;
+pub mod compare;
+pub mod parallel;
+pub mod html;
+pub mod multiqc;
 pub mod adapters;
+pub mod trimming;
+pub mod complexity;
+pub mod failed;
+pub mod polyx;
 pub mod primers;
 pub mod rrna;
 
+use adapters::AdapterContent;
 use anyhow::Result;
+use distributions::{ExpectedErrorsDistribution, GcDistribution, LengthDistribution};
+use duplication::DuplicationStats;
+use overrepresented::OverrepresentedSequence;
+use quality::{PositionComposition, PositionQuality};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
+use tiles::TileQualityReport;
+use trimming::{FailureReason, LengthRuleStats, TrimStep};
+use verdicts::ModuleVerdict;
 
 /// Trait for QC statistics structures
 pub trait QcStatsMarker: Clone + Serialize + for<'de> Deserialize<'de> + std::fmt::Debug {}
@@ -33,9 +73,39 @@ pub struct QcStats {
     pub mean_quality: f64,
     pub q30_bases: f64,
     pub complexity_score: f64,
+    /// Per-cycle quality distribution (FastQC "per base sequence quality")
+    #[serde(default)]
+    pub per_position_quality: Vec<PositionQuality>,
+    /// Per-cycle A/C/G/T/N percentages (FastQC "per base sequence content" and "N content")
+    #[serde(default)]
+    pub per_position_composition: Vec<PositionComposition>,
+    /// ASCII offset used to decode quality scores (33 or 64)
+    #[serde(default = "encoding::default_phred_offset")]
+    pub phred_offset: u8,
+    /// Most frequent read prefixes with their likely source
+    #[serde(default)]
+    pub overrepresented_sequences: Vec<OverrepresentedSequence>,
+    /// Duplication level histogram and deduplication estimate
+    #[serde(default)]
+    pub duplication: DuplicationStats,
+    /// Per-lane, per-tile mean quality matrix with outlier tiles flagged
+    #[serde(default)]
+    pub tile_quality: TileQualityReport,
+    /// Read length min/max/mean/N50 and binned histogram
+    #[serde(default)]
+    pub length_distribution: LengthDistribution,
+    /// Per-read GC histogram against a normal fit, with multimodal profiles flagged
+    #[serde(default)]
+    pub gc_distribution: GcDistribution,
+    /// Cumulative % of reads containing each adapter by position
+    #[serde(default)]
+    pub adapter_content: Vec<AdapterContent>,
+    /// PASS/WARN/FAIL per module against the configured limits
+    #[serde(default)]
+    pub verdicts: Vec<ModuleVerdict>,
 }
 
-/// PolyG trimming statistics
+/// PolyG (polyX) trimming statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolyGStats {
     pub sample_name: String,
@@ -44,6 +114,22 @@ pub struct PolyGStats {
     pub reads_discarded: u64,
     pub total_bases_removed: u64,
     pub average_trim_length: f64,
+    /// Tails removed per homopolymer base
+    #[serde(default)]
+    pub tails_by_base: BTreeMap<char, u64>,
+    #[serde(default)]
+    pub five_prime_tails: u64,
+    #[serde(default)]
+    pub three_prime_tails: u64,
+    /// Reads affected by HEADCROP, CROP and the length limits
+    #[serde(default)]
+    pub length_rules: LengthRuleStats,
+    /// Read lengths before trimming
+    #[serde(default)]
+    pub input_length_distribution: LengthDistribution,
+    /// Lengths of reads written after trimming (discarded reads excluded)
+    #[serde(default)]
+    pub output_length_distribution: LengthDistribution,
 }
 
 /// Quality filtering statistics
@@ -55,6 +141,67 @@ pub struct QualityFilterStats {
     pub reads_failed: u64,
     pub pass_rate: f64,
     pub quality_threshold: f64,
+    #[serde(default = "encoding::default_phred_offset")]
+    pub phred_offset: u8,
+    /// Minimum read length after trimming
+    #[serde(default)]
+    pub min_length: usize,
+    /// Reads affected by HEADCROP, CROP and the length limits
+    #[serde(default)]
+    pub length_rules: LengthRuleStats,
+    /// Reads shortened by at least one trimming step
+    #[serde(default)]
+    pub reads_trimmed: u64,
+    /// Bases removed by each trimming step
+    #[serde(default)]
+    pub bases_trimmed: BTreeMap<TrimStep, u64>,
+    /// Failed reads by the first check they failed
+    #[serde(default)]
+    pub reads_failed_by_reason: BTreeMap<FailureReason, u64>,
+    /// Input reads containing at least one N (or other ambiguity code)
+    #[serde(default)]
+    pub reads_with_n: u64,
+    /// Ambiguous bases in the input
+    #[serde(default)]
+    pub input_n_bases: u64,
+    /// Ambiguous bases left in the written reads
+    #[serde(default)]
+    pub output_n_bases: u64,
+    #[serde(default)]
+    pub max_n_count: Option<usize>,
+    #[serde(default)]
+    pub max_n_fraction: Option<f64>,
+    /// Maximum expected errors per read
+    #[serde(default)]
+    pub max_ee: Option<f64>,
+    /// Maximum expected errors per base
+    #[serde(default)]
+    pub max_ee_rate: Option<f64>,
+    /// Minimum biometal `complexity_score`
+    #[serde(default)]
+    pub min_complexity: Option<f64>,
+    /// Minimum trinucleotide entropy (0-1)
+    #[serde(default)]
+    pub min_entropy: Option<f64>,
+    /// DUST level (sdust `-t`), when masking
+    #[serde(default)]
+    pub dust_threshold: Option<f64>,
+    /// Written reads with at least one DUST-masked base
+    #[serde(default)]
+    pub reads_masked: u64,
+    #[serde(default)]
+    pub bases_masked: u64,
+    #[serde(default)]
+    pub input_length_distribution: LengthDistribution,
+    /// Lengths of reads written after trimming
+    #[serde(default)]
+    pub output_length_distribution: LengthDistribution,
+    /// Expected errors per input read
+    #[serde(default)]
+    pub input_expected_errors: ExpectedErrorsDistribution,
+    /// Expected errors per written read, after trimming
+    #[serde(default)]
+    pub output_expected_errors: ExpectedErrorsDistribution,
 }
 
 /// Read a FASTQ file and return basic metadata
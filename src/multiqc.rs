@@ -0,0 +1,514 @@
+//! MultiQC custom content output
+//!
+//! Writes `*_mqc.json` files that MultiQC picks up as custom content:
+//! - Each tool adds its headline metrics to the General Statistics table
+//! - `biometal-qc-summary` writes one aggregated table section for all samples
+//!
+//! Metric keys are stable so columns line up across runs.
+
+use crate::adapters::AdapterStats;
+use crate::compare::QcComparison;
+use crate::contamination::ContaminationReport;
+use crate::primers::PrimerRemovalStats;
+use crate::reporting::MultiSampleReport;
+use crate::rrna::RrnaRemovalStats;
+use crate::tiles::TileFilterStats;
+use crate::verdicts::Verdict;
+use crate::vlp::VlpReport;
+use crate::{PolyGStats, QcStats, QualityFilterStats};
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Suffix MultiQC uses to find custom content files
+pub const MQC_SUFFIX: &str = "_mqc.json";
+
+/// One column of a MultiQC table
+#[derive(Debug, Clone)]
+pub struct Metric {
+    pub key: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub value: f64,
+    pub format: &'static str,
+    pub suffix: Option<&'static str>,
+    pub max: Option<f64>,
+}
+
+impl Metric {
+    /// Whole-number count (reads, bases)
+    pub fn count(key: &'static str, title: &'static str, description: &'static str, value: u64) -> Self {
+        Self {
+            key,
+            title,
+            description,
+            value: value as f64,
+            format: "{:,.0f}",
+            suffix: None,
+            max: None,
+        }
+    }
+
+    /// Percentage on a 0-100 scale
+    pub fn percent(key: &'static str, title: &'static str, description: &'static str, value: f64) -> Self {
+        Self {
+            key,
+            title,
+            description,
+            value,
+            format: "{:,.2f}",
+            suffix: Some("%"),
+            max: Some(100.0),
+        }
+    }
+
+    /// Any other decimal value
+    pub fn value(key: &'static str, title: &'static str, description: &'static str, value: f64) -> Self {
+        Self {
+            key,
+            title,
+            description,
+            value,
+            format: "{:,.2f}",
+            suffix: None,
+            max: None,
+        }
+    }
+
+    fn header(&self) -> Value {
+        let mut header = json!({
+            "title": self.title,
+            "description": self.description,
+            "format": self.format,
+            "min": 0,
+        });
+        if let Some(suffix) = self.suffix {
+            header["suffix"] = json!(suffix);
+        }
+        if let Some(max) = self.max {
+            header["max"] = json!(max);
+        }
+        header
+    }
+}
+
+/// Where the section appears in the MultiQC report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlotType {
+    GeneralStats, // Columns in the General Statistics table
+    Table,        // A separate table section
+}
+
+/// A MultiQC custom content section with per-sample metrics
+#[derive(Debug, Clone)]
+pub struct MultiqcSection {
+    pub id: String,
+    pub section_name: String,
+    pub description: String,
+    pub plot_type: PlotType,
+    headers: Vec<Metric>, // First-seen metric per key, in insertion order
+    data: BTreeMap<String, BTreeMap<&'static str, f64>>,
+}
+
+impl MultiqcSection {
+    pub fn new(id: &str, section_name: &str, description: &str, plot_type: PlotType) -> Self {
+        Self {
+            id: id.to_string(),
+            section_name: section_name.to_string(),
+            description: description.to_string(),
+            plot_type,
+            headers: Vec::new(),
+            data: BTreeMap::new(),
+        }
+    }
+
+    /// Section that adds one tool's metrics to the General Statistics table
+    pub fn general_stats(tool: &str, description: &str) -> Self {
+        let id = format!("biometal_{}", tool.trim_start_matches("biometal-").replace('-', "_"));
+        Self::new(&id, tool, description, PlotType::GeneralStats)
+    }
+
+    /// Add (or extend) one sample's row
+    pub fn add_sample(&mut self, sample: &str, metrics: Vec<Metric>) {
+        let row = self.data.entry(sample.to_string()).or_default();
+        for metric in metrics {
+            row.insert(metric.key, metric.value);
+            if !self.headers.iter().any(|h| h.key == metric.key) {
+                self.headers.push(metric);
+            }
+        }
+    }
+
+    /// Custom content JSON as MultiQC expects it
+    pub fn to_json(&self) -> Value {
+        let data: Map<String, Value> = self
+            .data
+            .iter()
+            .map(|(sample, row)| {
+                let row: Map<String, Value> = row.iter().map(|(&key, &value)| (key.to_string(), json!(value))).collect();
+                (sample.clone(), Value::Object(row))
+            })
+            .collect();
+
+        let mut section = json!({
+            "id": self.id,
+            "section_name": self.section_name,
+            "description": self.description,
+            "data": data,
+        });
+
+        match self.plot_type {
+            // General stats columns are configured through a list of single-key objects
+            PlotType::GeneralStats => {
+                section["plot_type"] = json!("generalstats");
+                section["pconfig"] = Value::Array(
+                    self.headers
+                        .iter()
+                        .map(|metric| json!({ metric.key: metric.header() }))
+                        .collect(),
+                );
+            }
+            PlotType::Table => {
+                let headers: Map<String, Value> = self
+                    .headers
+                    .iter()
+                    .map(|metric| (metric.key.to_string(), metric.header()))
+                    .collect();
+                section["plot_type"] = json!("table");
+                section["pconfig"] = json!({ "id": format!("{}_table", self.id), "title": self.section_name });
+                section["headers"] = Value::Object(headers);
+            }
+        }
+
+        section
+    }
+
+    /// Write the section, adding the `_mqc.json` suffix if the path lacks it
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf> {
+        let path = mqc_path(path.as_ref());
+        std::fs::write(&path, serde_json::to_string_pretty(&self.to_json())?)?;
+        Ok(path)
+    }
+}
+
+/// Make sure a path ends in `_mqc.json` so MultiQC recognises it
+pub fn mqc_path(path: &Path) -> PathBuf {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("biometal");
+    if name.ends_with(MQC_SUFFIX) {
+        return path.to_path_buf();
+    }
+
+    let stem = name.strip_suffix(".json").unwrap_or(name);
+    path.with_file_name(format!("{}{}", stem, MQC_SUFFIX))
+}
+
+/// Sample name MultiQC should show for an input file (file name without FASTQ extensions)
+pub fn sample_name_from_path<P: AsRef<Path>>(path: P) -> String {
+    let name = path
+        .as_ref()
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+
+    let mut sample = name.strip_suffix(".gz").unwrap_or(name);
+    for extension in [".fastq", ".fq"] {
+        if let Some(stripped) = sample.strip_suffix(extension) {
+            sample = stripped;
+        }
+    }
+    sample.to_string()
+}
+
+/// Write one tool's metrics for one sample as a General Statistics section
+pub fn write_general_stats<P: AsRef<Path>>(
+    tool: &str,
+    description: &str,
+    sample: &str,
+    metrics: Vec<Metric>,
+    path: P,
+) -> Result<PathBuf> {
+    let mut section = MultiqcSection::general_stats(tool, description);
+    section.add_sample(sample, metrics);
+    section.write(path)
+}
+
+/// Headline metrics a stats structure contributes to MultiQC
+pub trait MultiqcMetrics {
+    fn multiqc_metrics(&self) -> Vec<Metric>;
+}
+
+impl MultiqcMetrics for QcStats {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::count("total_reads", "Reads", "Total reads analysed", self.total_reads),
+            Metric::value("mean_quality", "Mean Q", "Mean per-read Phred quality", self.mean_quality),
+            Metric::percent("q30_bases", "% Q30", "Bases with Phred quality >= 30", self.q30_bases),
+            Metric::percent("gc_content", "% GC", "Overall GC content", self.gc_content),
+            Metric::value("mean_length", "Mean length", "Mean read length (bp)", self.length_distribution.mean_length),
+            Metric::percent(
+                "percent_unique",
+                "% Unique",
+                "Reads remaining after exact-sequence deduplication (estimated)",
+                self.duplication.percent_remaining_if_deduplicated,
+            ),
+            Metric::percent(
+                "adapter_content",
+                "% Adapter",
+                "Highest cumulative adapter content at any position",
+                self.adapter_content.iter().map(|content| content.max_percent).fold(0.0, f64::max),
+            ),
+        ]
+    }
+}
+
+impl MultiqcMetrics for QcComparison {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::percent("read_retention", "% Reads kept", "Input reads present in the output", self.read_retention),
+            Metric::percent("base_retention", "% Bases kept", "Input bases present in the output", self.base_retention),
+            Metric::value("mean_quality_delta", "ΔMean Q", "Change in mean per-read Phred quality", self.mean_quality.delta),
+            Metric::value("q30_bases_delta", "Δ% Q30", "Change in % of bases at Phred >= 30", self.q30_bases.delta),
+        ]
+    }
+}
+
+impl MultiqcMetrics for ContaminationReport {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::percent("phix_percentage", "% PhiX", "Reads matching PhiX174", self.phix_percentage),
+            Metric::percent("vector_percentage", "% Vector", "Reads matching vector sequences", self.vector_percentage),
+        ]
+    }
+}
+
+impl MultiqcMetrics for VlpReport {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::value("vlp_success_score", "VLP score", "Composite VLP success score (0-1)", self.vlp_success_score),
+            Metric::value("complexity_diversity", "Complexity", "Mean sequence complexity", self.complexity_diversity),
+            Metric::count(
+                "gc_peaks",
+                "GC peaks",
+                "Separated peaks in the per-read GC distribution",
+                self.gc_distribution.peaks.len() as u64,
+            ),
+        ]
+    }
+}
+
+impl MultiqcMetrics for PolyGStats {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::percent(
+                "polyg_trimmed",
+                "% PolyG trimmed",
+                "Reads with a polyG tail removed",
+                percent(self.reads_trimmed, self.total_reads),
+            ),
+            Metric::percent(
+                "polyg_discarded",
+                "% PolyG discarded",
+                "Reads too short after polyG trimming",
+                percent(self.reads_discarded, self.total_reads),
+            ),
+        ]
+    }
+}
+
+impl MultiqcMetrics for QualityFilterStats {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        let bases_trimmed: u64 = self.bases_trimmed.values().sum();
+        let input_bases = self.input_length_distribution.mean_length * self.input_length_distribution.reads as f64;
+        vec![
+            Metric::percent(
+                "quality_pass_rate",
+                "% Quality pass",
+                "Reads passing the quality filter",
+                self.pass_rate,
+            ),
+            Metric::percent(
+                "quality_bases_trimmed",
+                "% Q-trimmed",
+                "Bases removed by quality trimming",
+                if input_bases > 0.0 { bases_trimmed as f64 / input_bases * 100.0 } else { 0.0 },
+            ),
+            Metric::value(
+                "quality_mean_ee",
+                "Mean EE",
+                "Mean expected errors per written read",
+                self.output_expected_errors.mean_expected_errors,
+            ),
+        ]
+    }
+}
+
+impl MultiqcMetrics for AdapterStats {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::percent(
+                "reads_with_adapters",
+                "% Adapter",
+                "Reads with an Illumina adapter",
+                percent(self.reads_with_adapters as u64, self.total_reads as u64),
+            ),
+            Metric::count(
+                "adapter_bases_trimmed",
+                "Adapter bp",
+                "Bases removed by adapter trimming",
+                self.total_bases_trimmed as u64,
+            ),
+        ]
+    }
+}
+
+impl MultiqcMetrics for PrimerRemovalStats {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::percent(
+                "reads_with_forward_primers",
+                "% Fwd primer",
+                "Reads with a 5' primer",
+                percent(self.reads_with_forward_primers as u64, self.total_reads as u64),
+            ),
+            Metric::percent(
+                "reads_with_rc_primers",
+                "% RC primer",
+                "Reads with a 3' reverse-complement primer",
+                percent(self.reads_with_rc_primers as u64, self.total_reads as u64),
+            ),
+            Metric::percent(
+                "primer_contamination",
+                "% Primer cross-contam",
+                "Reads with unexpected primer variants",
+                self.contamination_level,
+            ),
+        ]
+    }
+}
+
+impl MultiqcMetrics for RrnaRemovalStats {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        vec![Metric::percent(
+            "rrna_detection_rate",
+            "% rRNA",
+            "Reads identified as rRNA",
+            self.rrna_detection_rate,
+        )]
+    }
+}
+
+impl MultiqcMetrics for TileFilterStats {
+    fn multiqc_metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::percent("tile_removal_rate", "% Bad tiles", "Reads removed from excluded tiles", self.removal_rate),
+            Metric::count(
+                "tiles_excluded",
+                "Tiles excluded",
+                "Outlier or listed tiles removed",
+                self.excluded_tiles.len() as u64,
+            ),
+        ]
+    }
+}
+
+/// Aggregated table of every sample's quality, contamination and VLP metrics
+pub fn summary_section(report: &MultiSampleReport) -> MultiqcSection {
+    let mut section = MultiqcSection::new(
+        "biometal_qc_summary",
+        "biometal-qc-summary",
+        "Per-sample QC metrics and overall pass/fail from biometal-qc-summary",
+        PlotType::Table,
+    );
+
+    for sample in &report.samples {
+        let mut metrics = sample.quality_stats.multiqc_metrics();
+        metrics.extend(sample.contamination_report.multiqc_metrics());
+        metrics.extend(sample.vlp_report.multiqc_metrics());
+        metrics.push(Metric::count(
+            "qc_pass",
+            "QC pass",
+            "1 if the sample passed every QC threshold, 0 otherwise",
+            sample.overall_pass as u64,
+        ));
+        let verdicts = &sample.quality_stats.verdicts;
+        metrics.push(Metric::count(
+            "module_warn",
+            "Modules WARN",
+            "Quality modules graded WARN against the limits",
+            verdicts.iter().filter(|v| v.verdict == Verdict::Warn).count() as u64,
+        ));
+        metrics.push(Metric::count(
+            "module_fail",
+            "Modules FAIL",
+            "Quality modules graded FAIL against the limits",
+            verdicts.iter().filter(|v| v.verdict == Verdict::Fail).count() as u64,
+        ));
+        // Quality stats carry the file stem ("x.fastq" for x.fastq.gz); key rows
+        // the way every per-tool section does so a sample gets one row
+        section.add_sample(&sample_name_from_path(&sample.quality_stats.sample_name), metrics);
+    }
+
+    section
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total > 0 {
+        part as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::QualityEncoding;
+    use crate::quality::QualityAccumulator;
+    use crate::reporting::{QcReporter, SampleQcReport};
+
+    #[test]
+    fn test_general_stats_section_shape() {
+        let mut section = MultiqcSection::general_stats("biometal-adapter-trim", "Adapter trimming");
+        section.add_sample("S1", vec![Metric::percent("reads_with_adapters", "% Adapter", "", 12.5)]);
+
+        let json = section.to_json();
+        assert_eq!(json["id"], "biometal_adapter_trim");
+        assert_eq!(json["plot_type"], "generalstats");
+        assert_eq!(json["data"]["S1"]["reads_with_adapters"], 12.5);
+        assert_eq!(json["pconfig"][0]["reads_with_adapters"]["suffix"], "%");
+        assert_eq!(mqc_path(Path::new("out/stats.json")), PathBuf::from("out/stats_mqc.json"));
+    }
+
+    #[test]
+    fn test_summary_rows_match_per_tool_sample_names() {
+        let quality_stats = QualityAccumulator::new("x.fastq", QualityEncoding::Phred33, 1).finish();
+        let sample = SampleQcReport {
+            contamination_report: ContaminationReport {
+                sample_name: "x.fastq".to_string(),
+                total_reads: 0,
+                phix_reads: 0,
+                vector_reads: 0,
+                phix_percentage: 0.0,
+                vector_percentage: 0.0,
+            },
+            vlp_report: VlpReport {
+                sample_name: "x.fastq".to_string(),
+                total_reads: 0,
+                gc_distribution_score: 0.0,
+                complexity_diversity: 0.0,
+                compositional_evenness: 0.0,
+                vlp_success_score: 0.0,
+                gc_distribution: Default::default(),
+            },
+            quality_stats,
+            overall_pass: false,
+        };
+        let report = QcReporter::default().generate_report(vec![sample]);
+
+        let sample_name = sample_name_from_path("reads/x.fastq.gz");
+        assert_eq!(sample_name, "x");
+        let json = summary_section(&report).to_json();
+        assert_eq!(json["data"].as_object().unwrap().keys().collect::<Vec<_>>(), vec![&sample_name]);
+    }
+}
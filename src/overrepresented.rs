@@ -0,0 +1,174 @@
+//! Overrepresented sequence detection
+//!
+//! FastQC-style "overrepresented sequences" module:
+//! - Counts read prefixes in bounded memory (see `counting`)
+//! - Reports the most frequent prefixes with counts and percentages
+//! - Matches each hit against Illumina adapters and PhiX/vector patterns
+//!   using biometal `has_pattern`
+
+use crate::adapters::illumina_adapters;
+use crate::contamination::ContaminationScreener;
+use crate::counting::BoundedCounter;
+use biometal::operations::has_pattern;
+use serde::{Deserialize, Serialize};
+
+/// Read prefix length counted per read (FastQC truncates to 50bp)
+pub const DEFAULT_PREFIX_LENGTH: usize = 50;
+
+/// Distinct prefixes tracked before new ones are ignored
+pub const DEFAULT_MAX_TRACKED: usize = 100_000;
+
+/// Minimum percentage of reads for a prefix to be reported
+pub const DEFAULT_MIN_PERCENTAGE: f64 = 0.1;
+
+/// Maximum number of overrepresented sequences reported
+pub const DEFAULT_TOP_HITS: usize = 20;
+
+/// Minimum exact overlap with a known sequence to call a source
+const SOURCE_MATCH_LENGTH: usize = 20;
+
+/// Label used when a hit matches no known sequence
+pub const NO_HIT: &str = "No Hit";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverrepresentedSequence {
+    pub sequence: String,
+    pub count: u64,
+    pub percentage: f64,
+    pub possible_source: String,
+}
+
+/// Bounded-memory prefix counter for overrepresented sequence detection
+#[derive(Debug, Clone)]
+pub struct OverrepresentedCounter {
+    pub prefix_length: usize,
+    pub min_percentage: f64,
+    pub top_hits: usize,
+    counts: BoundedCounter<Vec<u8>>,
+}
+
+impl Default for OverrepresentedCounter {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_PREFIX_LENGTH,
+            DEFAULT_MAX_TRACKED,
+            DEFAULT_MIN_PERCENTAGE,
+            DEFAULT_TOP_HITS,
+        )
+    }
+}
+
+impl OverrepresentedCounter {
+    pub fn new(prefix_length: usize, max_tracked: usize, min_percentage: f64, top_hits: usize) -> Self {
+        Self {
+            prefix_length,
+            min_percentage,
+            top_hits,
+            counts: BoundedCounter::new(max_tracked),
+        }
+    }
+
+    /// Count the prefix of one read
+    pub fn add(&mut self, sequence: &[u8]) {
+        let prefix_end = sequence.len().min(self.prefix_length);
+        self.counts.add(sequence[..prefix_end].to_ascii_uppercase());
+    }
+
+    /// Fold in a counter built over later reads
+    pub fn merge(&mut self, other: Self) {
+        self.counts.merge(other.counts);
+    }
+
+    /// Report the most frequent prefixes above `min_percentage` of all reads
+    pub fn finish(&self) -> Vec<OverrepresentedSequence> {
+        let total_reads = self.counts.observations();
+        if total_reads == 0 {
+            return Vec::new();
+        }
+
+        let mut hits: Vec<_> = self
+            .counts
+            .iter()
+            .filter(|(_, tracked)| {
+                (tracked.count as f64 / total_reads as f64) * 100.0 >= self.min_percentage
+            })
+            .collect();
+
+        // Most frequent first; ties keep first-seen order so output is stable
+        hits.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.1.first_seen.cmp(&b.1.first_seen)));
+
+        hits.into_iter()
+            .take(self.top_hits)
+            .map(|(prefix, tracked)| OverrepresentedSequence {
+                sequence: String::from_utf8_lossy(prefix).to_string(),
+                count: tracked.count,
+                percentage: (tracked.count as f64 / total_reads as f64) * 100.0,
+                possible_source: identify_source(prefix),
+            })
+            .collect()
+    }
+}
+
+/// Match a sequence against known adapter, PhiX and vector sequences
+///
+/// A source is called when the sequence (or its reverse complement) shares an
+/// exact stretch of at least 20bp with the known sequence.
+pub fn identify_source(sequence: &[u8]) -> String {
+    let reverse_complement = reverse_complement(sequence);
+
+    for (name, adapter) in illumina_adapters() {
+        if shares_stretch(sequence, adapter.as_bytes()) || shares_stretch(&reverse_complement, adapter.as_bytes()) {
+            return format!("{} adapter", name);
+        }
+    }
+
+    let known_contaminants = [
+        ("PhiX174", ContaminationScreener::get_phix_patterns()),
+        ("Vector", ContaminationScreener::get_vector_patterns()),
+    ];
+    for (name, patterns) in known_contaminants {
+        for pattern in patterns {
+            if shares_stretch(sequence, pattern.as_bytes()) || shares_stretch(&reverse_complement, pattern.as_bytes()) {
+                return name.to_string();
+            }
+        }
+    }
+
+    NO_HIT.to_string()
+}
+
+/// Whether any window of `query` (20bp, or shorter if either sequence is) occurs in `reference`
+fn shares_stretch(query: &[u8], reference: &[u8]) -> bool {
+    let window = SOURCE_MATCH_LENGTH.min(query.len()).min(reference.len());
+    if window == 0 {
+        return false;
+    }
+
+    query.windows(window).any(|w| has_pattern(reference, w))
+}
+
+fn reverse_complement(sequence: &[u8]) -> Vec<u8> {
+    sequence
+        .iter()
+        .rev()
+        .map(|&base| match base {
+            b'A' => b'T',
+            b'T' => b'A',
+            b'G' => b'C',
+            b'C' => b'G',
+            other => other,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapter_dimer_is_identified() {
+        let dimer = b"AGATCGGAAGAGCACACGTCTGAACTCCAGTCACGTACGTAATCTCGTATG";
+        assert_eq!(identify_source(dimer), "Illumina Universal adapter");
+        assert_eq!(identify_source(b"ACGTTGCAACGTTGCAACGTTGCAACGT"), NO_HIT);
+    }
+}
@@ -0,0 +1,196 @@
+//! Multithreaded record processing
+//!
+//! Records are parsed on the calling thread in fixed-size batches. Each batch
+//! is added to a fresh accumulator on a worker thread, and the batch
+//! accumulators are merged back in input order. Batch boundaries do not depend
+//! on the thread count, so results are identical for any `--threads` value.
+//! The next round of batches is read while the current one is being processed.
+
+use crate::contamination::ContaminationAccumulator;
+use crate::quality::QualityAccumulator;
+use crate::saturation::ExactDuplicateCounter;
+use crate::vlp::VlpAccumulator;
+use anyhow::Result;
+use biometal::io::{DataSource, FastqStream};
+use biometal::FastqRecord;
+use std::path::Path;
+
+/// Records per batch handed to a worker
+pub const DEFAULT_BATCH_SIZE: usize = 10_000;
+
+/// Streaming statistics that can be built per batch and merged in order
+pub trait RecordAccumulator: Send {
+    fn add(&mut self, record: &FastqRecord);
+    fn merge(&mut self, other: Self);
+}
+
+impl RecordAccumulator for QualityAccumulator {
+    fn add(&mut self, record: &FastqRecord) {
+        QualityAccumulator::add(self, record);
+    }
+
+    fn merge(&mut self, other: Self) {
+        QualityAccumulator::merge(self, other);
+    }
+}
+
+impl RecordAccumulator for ContaminationAccumulator {
+    fn add(&mut self, record: &FastqRecord) {
+        ContaminationAccumulator::add(self, record);
+    }
+
+    fn merge(&mut self, other: Self) {
+        ContaminationAccumulator::merge(self, other);
+    }
+}
+
+impl RecordAccumulator for ExactDuplicateCounter {
+    fn add(&mut self, record: &FastqRecord) {
+        ExactDuplicateCounter::add(self, record);
+    }
+
+    fn merge(&mut self, other: Self) {
+        ExactDuplicateCounter::merge(self, other);
+    }
+}
+
+impl RecordAccumulator for VlpAccumulator {
+    fn add(&mut self, record: &FastqRecord) {
+        VlpAccumulator::add(self, record);
+    }
+
+    fn merge(&mut self, other: Self) {
+        VlpAccumulator::merge(self, other);
+    }
+}
+
+/// Stream a FASTQ file through `threads` workers, `batch_size` records at a time
+///
+/// `new_accumulator` creates the empty accumulator used for the result and for every batch.
+pub fn accumulate_fastq<P, A, F>(path: P, threads: usize, batch_size: usize, new_accumulator: F) -> Result<A>
+where
+    P: AsRef<Path>,
+    A: RecordAccumulator,
+    F: Fn() -> A + Sync,
+{
+    let threads = threads.max(1);
+    let batch_size = batch_size.max(1);
+
+    let data_source = DataSource::from_path(path.as_ref());
+    let mut fastq_stream = FastqStream::new(data_source)?;
+
+    let mut total = new_accumulator();
+    let mut round = read_round(&mut fastq_stream, threads, batch_size)?;
+
+    while !round.is_empty() {
+        let new_accumulator = &new_accumulator;
+        let (batch_results, next_round) = std::thread::scope(|scope| {
+            let workers: Vec<_> = round
+                .iter()
+                .map(|batch| {
+                    scope.spawn(move || {
+                        let mut accumulator = new_accumulator();
+                        for record in batch {
+                            accumulator.add(record);
+                        }
+                        accumulator
+                    })
+                })
+                .collect();
+
+            // Parse the next round while the workers run
+            let next_round = read_round(&mut fastq_stream, threads, batch_size);
+
+            let batch_results: Vec<A> = workers
+                .into_iter()
+                .map(|worker| worker.join().expect("record worker thread panicked"))
+                .collect();
+            (batch_results, next_round)
+        });
+
+        // Merge in input order so the result does not depend on scheduling
+        for batch in batch_results {
+            total.merge(batch);
+        }
+        round = next_round?;
+    }
+
+    Ok(total)
+}
+
+/// Read up to `threads` batches of `batch_size` records
+fn read_round(fastq_stream: &mut FastqStream, threads: usize, batch_size: usize) -> Result<Vec<Vec<FastqRecord>>> {
+    let mut round = Vec::with_capacity(threads);
+
+    while round.len() < threads {
+        let mut batch = Vec::with_capacity(batch_size);
+        for record_result in fastq_stream.by_ref().take(batch_size) {
+            batch.push(record_result?);
+        }
+
+        let last_batch = batch.len() < batch_size;
+        if !batch.is_empty() {
+            round.push(batch);
+        }
+        if last_batch {
+            break;
+        }
+    }
+
+    Ok(round)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::QualityEncoding;
+    use serde::Serialize;
+    use std::io::Write;
+
+    /// Run `new_accumulator` over the file with 1 and 4 threads and compare the results as JSON
+    fn assert_thread_independent<A, R, F, G>(path: &Path, new_accumulator: F, finish: G)
+    where
+        A: RecordAccumulator,
+        R: Serialize,
+        F: Fn() -> A + Sync,
+        G: Fn(&A) -> R,
+    {
+        let single = accumulate_fastq(path, 1, 100, &new_accumulator).unwrap();
+        let parallel = accumulate_fastq(path, 4, 100, &new_accumulator).unwrap();
+        assert_eq!(
+            serde_json::to_value(finish(&single)).unwrap(),
+            serde_json::to_value(finish(&parallel)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_results_do_not_depend_on_thread_count() {
+        // 2,050 reads: 21 batches of 100, the last one partial
+        let path = std::env::temp_dir().join(format!("biometal_parallel_{}.fastq", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        for read in 0..2050 {
+            let length = 60 + read % 41;
+            let (sequence, quality): (String, String) = (0..length)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    (b"ACGTN"[(seed % 5) as usize] as char, (b'#' + (seed >> 8) as u8 % 40) as char)
+                })
+                .unzip();
+            writeln!(file, "@A00123:8:HXXX:1:{}:{}:{} 1:N:0:1", 1101 + read % 3, read, read * 7).unwrap();
+            writeln!(file, "{}\n+\n{}", sequence, quality).unwrap();
+        }
+        drop(file);
+
+        assert_thread_independent(
+            &path,
+            || QualityAccumulator::new("sample", QualityEncoding::Phred33, 1),
+            QualityAccumulator::finish,
+        );
+        assert_thread_independent(&path, || ContaminationAccumulator::new("sample", 1), ContaminationAccumulator::finish);
+        assert_thread_independent(&path, || VlpAccumulator::new("sample", (0.35, 0.65), 1), VlpAccumulator::finish);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+//! Homopolymer (polyX) tail detection for biometal-polyg-trim
+//!
+//! A tail is a run of one base reaching the end of the read. By default the
+//! run must be exact; with `mismatches_per_window` set it tolerates miscalls
+//! the way fastp `--trim_poly_x` does (1 per 8 bases of tail, capped at
+//! `max_mismatches`). The budget is checked once a full window has been read,
+//! so a miscall inside the first window does not end the tail early, and a
+//! tail always stops on a matching base. N counts as a mismatch.
+//!
+//! The 3' tail is found first; the 5' tail is searched in what is left.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Which read ends to search for tails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TailEnds {
+    ThreePrime,
+    FivePrime,
+    Both,
+}
+
+impl FromStr for TailEnds {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "3" | "3'" | "3prime" => Ok(TailEnds::ThreePrime),
+            "5" | "5'" | "5prime" => Ok(TailEnds::FivePrime),
+            "both" => Ok(TailEnds::Both),
+            other => anyhow::bail!("Unknown tail end '{}' (expected 3, 5 or both)", other),
+        }
+    }
+}
+
+/// A homopolymer tail found at one end of a read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolyXTail {
+    pub base: char,
+    pub length: usize,
+}
+
+/// Bases kept after polyX trimming and the tails removed
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolyXOutcome {
+    pub keep: Range<usize>,
+    pub five_prime: Option<PolyXTail>,
+    pub three_prime: Option<PolyXTail>,
+}
+
+/// PolyX tail trimmer
+#[derive(Debug, Clone)]
+pub struct PolyXTrimmer {
+    pub bases: Vec<u8>, // Homopolymer bases to look for, in tie-break order
+    pub ends: TailEnds,
+    pub min_length: usize,
+    pub mismatch_window: usize,
+    pub mismatches_per_window: usize,
+    pub max_mismatches: usize,
+}
+
+impl Default for PolyXTrimmer {
+    fn default() -> Self {
+        Self {
+            bases: vec![b'G'],
+            ends: TailEnds::ThreePrime,
+            min_length: 10,
+            mismatch_window: 8, // fastp: one mismatch per 8 bases
+            mismatches_per_window: 0, // Exact runs unless enabled
+            max_mismatches: 5, // fastp cap
+        }
+    }
+}
+
+impl PolyXTrimmer {
+    /// Find and remove tails from one read
+    pub fn trim(&self, sequence: &[u8]) -> PolyXOutcome {
+        let mut keep = 0..sequence.len();
+        let mut three_prime = None;
+        let mut five_prime = None;
+
+        if matches!(self.ends, TailEnds::ThreePrime | TailEnds::Both) {
+            three_prime = self.longest_tail(sequence.iter().rev());
+            if let Some(tail) = three_prime {
+                keep.end -= tail.length;
+            }
+        }
+        if matches!(self.ends, TailEnds::FivePrime | TailEnds::Both) {
+            five_prime = self.longest_tail(sequence[keep.clone()].iter());
+            if let Some(tail) = five_prime {
+                keep.start += tail.length;
+            }
+        }
+
+        PolyXOutcome {
+            keep,
+            five_prime,
+            three_prime,
+        }
+    }
+
+    /// Longest qualifying tail over the configured bases, reading inwards from one end
+    fn longest_tail<'a>(&self, inward: impl Iterator<Item = &'a u8> + Clone) -> Option<PolyXTail> {
+        self.bases
+            .iter()
+            .map(|&base| (base, self.tail_length(inward.clone(), base)))
+            .filter(|&(_, length)| length >= self.min_length)
+            .fold(None, |best: Option<PolyXTail>, (base, length)| match best {
+                Some(best) if best.length >= length => Some(best),
+                _ => Some(PolyXTail {
+                    base: base as char,
+                    length,
+                }),
+            })
+    }
+
+    fn tail_length<'a>(&self, inward: impl Iterator<Item = &'a u8>, base: u8) -> usize {
+        let window = self.mismatch_window.max(1);
+        let mut mismatches = 0;
+        let mut length = 0;
+
+        for (index, &read_base) in inward.enumerate() {
+            let examined = index + 1;
+            let matched = read_base.eq_ignore_ascii_case(&base);
+            if !matched {
+                mismatches += 1;
+            }
+
+            let allowed = (examined / window * self.mismatches_per_window).min(self.max_mismatches);
+            if mismatches <= allowed {
+                if matched {
+                    length = examined;
+                }
+            } else if examined >= window {
+                break;
+            }
+        }
+
+        length
+    }
+}
+
+/// Parse the homopolymer bases to trim, e.g. `G` or `ACGT`
+pub fn parse_polyx_bases(bases: &str) -> Result<Vec<u8>> {
+    let mut parsed: Vec<u8> = Vec::new();
+    for base in bases.bytes().map(|base| base.to_ascii_uppercase()) {
+        if !matches!(base, b'A' | b'C' | b'G' | b'T') {
+            anyhow::bail!("PolyX bases must be A, C, G or T, got '{}'", base as char);
+        }
+        if !parsed.contains(&base) {
+            parsed.push(base);
+        }
+    }
+    if parsed.is_empty() {
+        anyhow::bail!("No polyX bases given");
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tails_tolerate_miscalls() {
+        // NovaSeq tail with one miscalled base: the default exact scan stops at
+        // the A, leaving a 9 base run that is too short to trim
+        let read = b"ACGTTGCAAGCTTCCAGGGGGGGAGGGGGGGGG";
+        assert_eq!(PolyXTrimmer::default().trim(read).keep, 0..33);
+
+        let trimmer = PolyXTrimmer {
+            mismatches_per_window: 1,
+            ..PolyXTrimmer::default()
+        };
+        let outcome = trimmer.trim(read);
+        assert_eq!(outcome.keep, 0..16);
+        assert_eq!(outcome.three_prime, Some(PolyXTail { base: 'G', length: 17 }));
+
+        // Too many miscalls for the budget, and runs that do not reach the end
+        assert_eq!(trimmer.trim(b"ACGTTGCAAGGAGAGGAGG").keep, 0..19);
+        assert_eq!(trimmer.trim(b"GGGGGGGGGGGGACGTTGCA").keep, 0..20);
+
+        // PolyA on the 3' end and polyT on the 5' end
+        let both = PolyXTrimmer {
+            bases: parse_polyx_bases("at").unwrap(),
+            ends: TailEnds::Both,
+            ..trimmer.clone()
+        };
+        let outcome = both.trim(b"TTTTTTTTTTTTACGTTGCAAGCTTCGAAAAAAAAAAAA");
+        assert_eq!(outcome.keep, 12..27);
+        assert_eq!(outcome.five_prime.map(|tail| tail.base), Some('T'));
+        assert_eq!(outcome.three_prime.map(|tail| tail.length), Some(12));
+        assert!(parse_polyx_bases("GN").is_err());
+    }
+}
@@ -6,6 +6,8 @@
 //! - Two-step process: forward primers (5' trimming) + reverse complement primers (3' trimming)
 //! - Uses biometal MotifFinder for pattern detection and trimming operations
 
+use crate::distributions::{LengthDistribution, LengthDistributionAccumulator};
+use crate::trimming::{LengthRuleStats, LengthRules};
 use crate::QcStatsMarker;
 use anyhow::Result;
 use biometal::alignment::{MotifFinder, MotifPattern, MotifMatch};
@@ -26,6 +28,12 @@ pub struct PrimerRemovalStats {
     pub rc_primers_found: HashMap<String, usize>,
     pub total_bases_trimmed: usize,
     pub contamination_level: f64, // Percentage of reads with unexpected primer variants
+    #[serde(default)]
+    pub length_rules: LengthRuleStats,
+    #[serde(default)]
+    pub input_length_distribution: LengthDistribution,
+    #[serde(default)]
+    pub output_length_distribution: LengthDistribution,
 }
 
 impl Default for PrimerRemovalStats {
@@ -39,6 +47,9 @@ impl Default for PrimerRemovalStats {
             rc_primers_found: HashMap::new(),
             total_bases_trimmed: 0,
             contamination_level: 0.0,
+            length_rules: LengthRuleStats::default(),
+            input_length_distribution: LengthDistribution::default(),
+            output_length_distribution: LengthDistribution::default(),
         }
     }
 }
@@ -51,6 +62,7 @@ pub struct PrimerRemover {
     pub min_match_length: usize,  // Minimum k-mer size (BBDuk's mink=9)
     pub max_match_length: usize,  // Maximum k-mer size (BBDuk's k=16)
     pub contamination_threshold: f64, // Threshold for flagging cross-contamination
+    pub length_rules: LengthRules,    // Crops before primer search, limits on the trimmed read
 }
 
 impl Default for PrimerRemover {
@@ -59,6 +71,7 @@ impl Default for PrimerRemover {
             min_match_length: 9,   // BBDuk mink=9
             max_match_length: 16,  // BBDuk k=16
             contamination_threshold: 0.05, // 5% contamination threshold
+            length_rules: LengthRules::default(),
         }
     }
 }
@@ -70,6 +83,7 @@ impl PrimerRemover {
             min_match_length,
             max_match_length,
             contamination_threshold,
+            length_rules: LengthRules::default(),
         }
     }
 
@@ -218,11 +232,15 @@ impl PrimerRemover {
         let fastq_stream = FastqStream::new(data_source)?;
 
         let mut processed_records = Vec::new();
+        let mut input_lengths = LengthDistributionAccumulator::default();
+        let mut output_lengths = LengthDistributionAccumulator::default();
 
         // Process records in streaming fashion
         for record_result in fastq_stream {
             let record = record_result?;
             stats.total_reads += 1;
+            input_lengths.add(record.sequence.len());
+            let record = self.length_rules.crop_record(record, &mut stats.length_rules);
 
             // Step 1: Remove forward primers (5' end trimming like BBDuk ktrim="l")
             let mut current_record = record.clone();
@@ -268,11 +286,17 @@ impl PrimerRemover {
                 }
             }
 
+            if self.length_rules.check(current_record.sequence.len(), &mut stats.length_rules).is_some() {
+                continue;
+            }
+            output_lengths.add(current_record.sequence.len());
             processed_records.push(current_record);
         }
 
         // Calculate contamination level (cross-contamination detection)
         stats.contamination_level = self.calculate_contamination_level(&stats);
+        stats.input_length_distribution = input_lengths.finish();
+        stats.output_length_distribution = output_lengths.finish();
 
         // Write output if requested
         if let Some(output_path) = output_path {
@@ -5,17 +5,35 @@
 //! - gc_content: For GC content calculation
 //! - quality_filter: For quality score analysis
 //! - complexity: For sequence complexity assessment
+//!
+//! Quality bytes are converted with the detected (or overridden) Phred offset,
+//! see `encoding`.
 
+use crate::adapters::AdapterContentAccumulator;
+use crate::distributions::{GcDistributionAccumulator, LengthDistributionAccumulator};
+use crate::duplication::DuplicationEstimator;
+use crate::encoding::{mean_phred, EncodingDetector, QualityEncoding};
+use crate::overrepresented::OverrepresentedCounter;
+use crate::parallel::{accumulate_fastq, DEFAULT_BATCH_SIZE};
+use crate::tiles::TileQualityAccumulator;
+use crate::verdicts::QcLimits;
 use crate::QcStats;
 use anyhow::Result;
-use biometal::io::{DataSource, FastqStream};
-use biometal::operations::{complexity_score, gc_content, mean_quality};
+use biometal::operations::{complexity_score, gc_content};
+use biometal::FastqRecord;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+/// Highest Phred score tracked per position; higher scores are clamped into the top bin
+const MAX_TRACKED_PHRED: usize = 60;
+
 /// Quality statistics calculator using biometal primitives
 pub struct QualityAnalyzer {
     pub min_quality: u8,
     pub min_length: usize,
+    pub encoding: Option<QualityEncoding>, // None = detect from the input
+    pub threads: usize,
+    pub limits: QcLimits, // PASS/WARN/FAIL thresholds for the module verdicts
 }
 
 impl Default for QualityAnalyzer {
@@ -23,6 +41,9 @@ impl Default for QualityAnalyzer {
         Self {
             min_quality: 20,
             min_length: 50,
+            encoding: None,
+            threads: 1,
+            limits: QcLimits::default(),
         }
     }
 }
@@ -32,6 +53,9 @@ impl QualityAnalyzer {
         Self {
             min_quality,
             min_length,
+            encoding: None,
+            threads: 1,
+            limits: QcLimits::default(),
         }
     }
 
@@ -45,96 +69,203 @@ impl QualityAnalyzer {
             .unwrap_or("unknown")
             .to_string();
 
-        // Initialize counters
-        let mut total_reads = 0u64;
-        let mut total_bases = 0u64;
-        let mut gc_count = 0u64;
-        let mut quality_sum = 0f64;
-        let mut q30_bases_count = 0u64;
-        let mut complexity_sum = 0f64;
-
-        // Create biometal data source and stream
-        let data_source = DataSource::from_path(&fastq_path);
-        let fastq_stream = FastqStream::new(data_source)?;
-
-        // Process records using biometal streaming
-        for record_result in fastq_stream {
-            let record = record_result?;
-
-            // Skip empty records
-            if record.is_empty() || record.sequence.len() < self.min_length {
-                continue;
-            }
+        let encoding = match self.encoding {
+            Some(encoding) => encoding,
+            None => EncodingDetector::default().detect(&fastq_path)?,
+        };
 
-            total_reads += 1;
-            total_bases += record.sequence.len() as u64;
+        // Batches of records are processed on `threads` workers and merged in order
+        let accumulator = accumulate_fastq(&fastq_path, self.threads, DEFAULT_BATCH_SIZE, || {
+            self.accumulator(&sample_name, encoding)
+        })?;
 
-            // Use biometal gc_content primitive
-            let gc_content_ratio = gc_content(&record.sequence);
-            gc_count += (gc_content_ratio * record.sequence.len() as f64) as u64;
+        let mut stats = accumulator.finish();
+        stats.verdicts = self.limits.evaluate(&stats);
+        Ok(stats)
+    }
+
+    /// Empty accumulator using this analyzer's settings, for callers that
+    /// feed records themselves (sharded files, per-lane merges)
+    pub fn accumulator(&self, sample_name: &str, encoding: QualityEncoding) -> QualityAccumulator {
+        QualityAccumulator::new(sample_name, encoding, self.min_length)
+    }
+
+    /// Calculate quality distribution metrics
+    pub fn quality_distribution(&self, qualities: &[u8]) -> QualityDistribution {
+        let total = qualities.len();
+        let encoding = self.encoding.unwrap_or_default();
+        let q30_count = qualities.iter().filter(|&&q| encoding.phred(q) >= 30).count();
+        let q20_count = qualities.iter().filter(|&&q| encoding.phred(q) >= 20).count();
+
+        QualityDistribution {
+            q30_percent: (q30_count as f64 / total as f64) * 100.0,
+            q20_percent: (q20_count as f64 / total as f64) * 100.0,
+            mean_quality: mean_phred(qualities, encoding),
+        }
+    }
+}
 
-            // Use biometal mean_quality primitive
-            let record_mean_quality = mean_quality(&record.quality);
-            quality_sum += record_mean_quality;
+/// Streaming quality statistics that can be merged across shards
+///
+/// Feed records with `add`, combine accumulators built over consecutive parts
+/// of the input with `merge` (in input order), then call `finish` for `QcStats`.
+#[derive(Debug, Clone)]
+pub struct QualityAccumulator {
+    pub sample_name: String,
+    pub encoding: QualityEncoding,
+    pub min_length: usize,
+    total_reads: u64,
+    total_bases: u64,
+    gc_count: u64,
+    quality_sum: f64,
+    q30_bases_count: u64,
+    complexity_sum: f64,
+    position_quality: PositionQualityAccumulator,
+    position_composition: PositionCompositionAccumulator,
+    overrepresented: OverrepresentedCounter,
+    duplication: DuplicationEstimator,
+    tile_quality: TileQualityAccumulator,
+    lengths: LengthDistributionAccumulator,
+    gc_distribution: GcDistributionAccumulator,
+    adapter_content: AdapterContentAccumulator,
+}
 
-            // Count Q30 bases (quality >= 30, which is 63 in Phred+33)
-            let q30_count = record.quality.iter().filter(|&&q| q >= 63).count();
-            q30_bases_count += q30_count as u64;
+impl QualityAccumulator {
+    pub fn new(sample_name: &str, encoding: QualityEncoding, min_length: usize) -> Self {
+        Self {
+            sample_name: sample_name.to_string(),
+            encoding,
+            min_length,
+            total_reads: 0,
+            total_bases: 0,
+            gc_count: 0,
+            quality_sum: 0.0,
+            q30_bases_count: 0,
+            complexity_sum: 0.0,
+            position_quality: PositionQualityAccumulator::default(),
+            position_composition: PositionCompositionAccumulator::default(),
+            overrepresented: OverrepresentedCounter::default(),
+            duplication: DuplicationEstimator::default(),
+            tile_quality: TileQualityAccumulator::default(),
+            lengths: LengthDistributionAccumulator::default(),
+            gc_distribution: GcDistributionAccumulator::default(),
+            adapter_content: AdapterContentAccumulator::default(),
+        }
+    }
 
-            // Use biometal complexity primitive
-            let record_complexity = complexity_score(&record.sequence);
-            complexity_sum += record_complexity;
+    /// Add one record; empty reads and reads shorter than `min_length` are skipped
+    pub fn add(&mut self, record: &FastqRecord) {
+        if record.is_empty() || record.sequence.len() < self.min_length {
+            return;
         }
 
-        // Calculate final statistics
-        let gc_content_percent = if total_bases > 0 {
-            (gc_count as f64 / total_bases as f64) * 100.0
+        let encoding = self.encoding;
+        self.total_reads += 1;
+        self.total_bases += record.sequence.len() as u64;
+        self.lengths.add(record.sequence.len());
+
+        // Use biometal gc_content primitive
+        let gc_content_ratio = gc_content(&record.sequence);
+        self.gc_count += (gc_content_ratio * record.sequence.len() as f64) as u64;
+        self.gc_distribution.add(&record.sequence);
+
+        // Mean quality using the detected Phred offset
+        self.quality_sum += mean_phred(&record.quality, encoding);
+
+        // Count Q30 bases (quality >= 30 after removing the offset)
+        let q30_count = record
+            .quality
+            .iter()
+            .filter(|&&q| encoding.phred(q) >= 30)
+            .count();
+        self.q30_bases_count += q30_count as u64;
+
+        // Per-cycle quality histograms for the 3' drop-off profile
+        self.position_quality.add(&record.quality, encoding);
+
+        // Per-cycle A/C/G/T/N counts for composition bias and N spikes
+        self.position_composition.add(&record.sequence);
+
+        // Tile × cycle quality from the Illumina header coordinates
+        self.tile_quality.add(&record.id, &record.quality, encoding);
+
+        // Bounded prefix counts for overrepresented sequences
+        self.overrepresented.add(&record.sequence);
+
+        // Bounded exact-sequence hashes for duplication levels
+        self.duplication.add(&record.sequence);
+
+        // Earliest start of each adapter for the cumulative adapter content curve
+        self.adapter_content.add(record);
+
+        // Use biometal complexity primitive
+        self.complexity_sum += complexity_score(&record.sequence);
+    }
+
+    /// Fold in an accumulator built over the reads that follow this one
+    pub fn merge(&mut self, other: Self) {
+        self.total_reads += other.total_reads;
+        self.total_bases += other.total_bases;
+        self.gc_count += other.gc_count;
+        self.quality_sum += other.quality_sum;
+        self.q30_bases_count += other.q30_bases_count;
+        self.complexity_sum += other.complexity_sum;
+        self.position_quality.merge(other.position_quality);
+        self.position_composition.merge(other.position_composition);
+        self.overrepresented.merge(other.overrepresented);
+        self.duplication.merge(other.duplication);
+        self.tile_quality.merge(other.tile_quality);
+        self.lengths.merge(other.lengths);
+        self.gc_distribution.merge(other.gc_distribution);
+        self.adapter_content.merge(other.adapter_content);
+    }
+
+    /// Calculate final statistics
+    ///
+    /// Module verdicts are left empty; grade the result with `QcLimits::evaluate`.
+    pub fn finish(&self) -> QcStats {
+        let gc_content_percent = if self.total_bases > 0 {
+            (self.gc_count as f64 / self.total_bases as f64) * 100.0
         } else {
             0.0
         };
 
-        let mean_quality_score = if total_reads > 0 {
-            quality_sum / total_reads as f64
+        let mean_quality_score = if self.total_reads > 0 {
+            self.quality_sum / self.total_reads as f64
         } else {
             0.0
         };
 
-        let q30_percentage = if total_bases > 0 {
-            (q30_bases_count as f64 / total_bases as f64) * 100.0
+        let q30_percentage = if self.total_bases > 0 {
+            (self.q30_bases_count as f64 / self.total_bases as f64) * 100.0
         } else {
             0.0
         };
 
-        let avg_complexity = if total_reads > 0 {
-            complexity_sum / total_reads as f64
+        let avg_complexity = if self.total_reads > 0 {
+            self.complexity_sum / self.total_reads as f64
         } else {
             0.0
         };
 
-        let stats = QcStats {
-            sample_name,
-            total_reads,
-            total_bases,
+        QcStats {
+            sample_name: self.sample_name.clone(),
+            total_reads: self.total_reads,
+            total_bases: self.total_bases,
             gc_content: gc_content_percent,
             mean_quality: mean_quality_score,
             q30_bases: q30_percentage,
             complexity_score: avg_complexity,
-        };
-
-        Ok(stats)
-    }
-
-    /// Calculate quality distribution metrics
-    pub fn quality_distribution(&self, qualities: &[u8]) -> QualityDistribution {
-        let total = qualities.len();
-        // Convert Phred+33 to actual quality scores for thresholds
-        let q30_count = qualities.iter().filter(|&&q| q >= 63).count(); // Q30 = 30 + 33
-        let q20_count = qualities.iter().filter(|&&q| q >= 53).count(); // Q20 = 20 + 33
-
-        QualityDistribution {
-            q30_percent: (q30_count as f64 / total as f64) * 100.0,
-            q20_percent: (q20_count as f64 / total as f64) * 100.0,
-            mean_quality: mean_quality(qualities),
+            per_position_quality: self.position_quality.finish(),
+            per_position_composition: self.position_composition.finish(),
+            phred_offset: self.encoding.offset(),
+            overrepresented_sequences: self.overrepresented.finish(),
+            duplication: self.duplication.finish(),
+            tile_quality: self.tile_quality.finish(),
+            length_distribution: self.lengths.finish(),
+            gc_distribution: self.gc_distribution.finish(),
+            adapter_content: self.adapter_content.finish(),
+            verdicts: Vec::new(),
         }
     }
 }
@@ -144,4 +275,279 @@ pub struct QualityDistribution {
     pub q30_percent: f64,
     pub q20_percent: f64,
     pub mean_quality: f64,
-}
\ No newline at end of file
+}
+
+/// Quality distribution at a single read position (cycle), in Phred units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionQuality {
+    pub position: usize, // 1-based cycle number
+    pub reads: u64,
+    pub mean: f64,
+    pub median: f64,
+    pub lower_quartile: f64,
+    pub upper_quartile: f64,
+    pub percentile_10: f64,
+    pub percentile_90: f64,
+}
+
+/// Per-cycle quality histograms
+///
+/// Memory is bounded by read length rather than read count: each position keeps
+/// one counter per Phred score, so percentiles are exact without storing scores.
+#[derive(Debug, Clone, Default)]
+pub struct PositionQualityAccumulator {
+    histograms: Vec<[u64; MAX_TRACKED_PHRED + 1]>,
+}
+
+impl PositionQualityAccumulator {
+    /// Add one read's quality string
+    pub fn add(&mut self, quality: &[u8], encoding: QualityEncoding) {
+        if quality.len() > self.histograms.len() {
+            self.histograms.resize(quality.len(), [0; MAX_TRACKED_PHRED + 1]);
+        }
+
+        for (histogram, &q) in self.histograms.iter_mut().zip(quality) {
+            let phred = (encoding.phred(q) as usize).min(MAX_TRACKED_PHRED);
+            histogram[phred] += 1;
+        }
+    }
+
+    /// Fold in histograms from another set of reads
+    pub fn merge(&mut self, other: Self) {
+        if other.histograms.len() > self.histograms.len() {
+            self.histograms.resize(other.histograms.len(), [0; MAX_TRACKED_PHRED + 1]);
+        }
+
+        for (histogram, other) in self.histograms.iter_mut().zip(&other.histograms) {
+            for (count, other) in histogram.iter_mut().zip(other) {
+                *count += other;
+            }
+        }
+    }
+
+    /// Summarise each position into mean, quartiles and 10th/90th percentiles
+    pub fn finish(&self) -> Vec<PositionQuality> {
+        self.histograms
+            .iter()
+            .enumerate()
+            .map(|(i, histogram)| {
+                let reads: u64 = histogram.iter().sum();
+                let weighted: u64 = histogram
+                    .iter()
+                    .enumerate()
+                    .map(|(phred, &count)| phred as u64 * count)
+                    .sum();
+
+                PositionQuality {
+                    position: i + 1,
+                    reads,
+                    mean: if reads > 0 { weighted as f64 / reads as f64 } else { 0.0 },
+                    median: histogram_percentile(histogram, reads, 0.5),
+                    lower_quartile: histogram_percentile(histogram, reads, 0.25),
+                    upper_quartile: histogram_percentile(histogram, reads, 0.75),
+                    percentile_10: histogram_percentile(histogram, reads, 0.1),
+                    percentile_90: histogram_percentile(histogram, reads, 0.9),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Base composition at a single read position (cycle), as percentages of reads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionComposition {
+    pub position: usize, // 1-based cycle number
+    pub a: f64,
+    pub c: f64,
+    pub g: f64,
+    pub t: f64,
+    pub n: f64, // N and any other ambiguity code
+}
+
+/// Per-cycle base counts (A, C, G, T, N)
+#[derive(Debug, Clone, Default)]
+pub struct PositionCompositionAccumulator {
+    counts: Vec<[u64; 5]>,
+}
+
+impl PositionCompositionAccumulator {
+    /// Add one read's sequence
+    pub fn add(&mut self, sequence: &[u8]) {
+        if sequence.len() > self.counts.len() {
+            self.counts.resize(sequence.len(), [0; 5]);
+        }
+
+        for (counts, &base) in self.counts.iter_mut().zip(sequence) {
+            let index = match base {
+                b'A' | b'a' => 0,
+                b'C' | b'c' => 1,
+                b'G' | b'g' => 2,
+                b'T' | b't' => 3,
+                _ => 4,
+            };
+            counts[index] += 1;
+        }
+    }
+
+    /// Fold in counts from another set of reads
+    pub fn merge(&mut self, other: Self) {
+        if other.counts.len() > self.counts.len() {
+            self.counts.resize(other.counts.len(), [0; 5]);
+        }
+
+        for (counts, other) in self.counts.iter_mut().zip(&other.counts) {
+            for (count, other) in counts.iter_mut().zip(other) {
+                *count += other;
+            }
+        }
+    }
+
+    /// Convert counts into per-cycle percentages
+    pub fn finish(&self) -> Vec<PositionComposition> {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(i, counts)| {
+                let total: u64 = counts.iter().sum();
+                let percent = |count: u64| {
+                    if total > 0 {
+                        (count as f64 / total as f64) * 100.0
+                    } else {
+                        0.0
+                    }
+                };
+
+                PositionComposition {
+                    position: i + 1,
+                    a: percent(counts[0]),
+                    c: percent(counts[1]),
+                    g: percent(counts[2]),
+                    t: percent(counts[3]),
+                    n: percent(counts[4]),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Nearest-rank percentile of a Phred score histogram
+fn histogram_percentile(histogram: &[u64], total: u64, fraction: f64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    let rank = ((fraction * total as f64).ceil() as u64).max(1);
+    let mut cumulative = 0u64;
+    for (phred, &count) in histogram.iter().enumerate() {
+        cumulative += count;
+        if cumulative >= rank {
+            return phred as f64;
+        }
+    }
+
+    (histogram.len() - 1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(sequence: &[u8], quality: &[u8]) -> FastqRecord {
+        FastqRecord::new("read".to_string(), sequence.to_vec(), quality.to_vec())
+    }
+
+    #[test]
+    fn test_split_and_merge_matches_single_pass() {
+        // Lengths grow through the input, so the second shard has positions the first never saw
+        let records: Vec<FastqRecord> = (0..60usize)
+            .map(|i| {
+                let length = 20 + i / 2;
+                let sequence: Vec<u8> = (0..length).map(|j| b"ACGTN"[(i * 7 + j) % 5]).collect();
+                let quality: Vec<u8> = (0..length).map(|j| b'!' + ((i * 11 + j * 3) % 45) as u8).collect();
+                record(&sequence, &quality)
+            })
+            .collect();
+
+        let mut single = QualityAccumulator::new("sample", QualityEncoding::Phred33, 1);
+        let mut single_positions = PositionQualityAccumulator::default();
+        for record in &records {
+            single.add(record);
+            single_positions.add(&record.quality, QualityEncoding::Phred33);
+        }
+
+        let mut merged = QualityAccumulator::new("sample", QualityEncoding::Phred33, 1);
+        let mut merged_positions = PositionQualityAccumulator::default();
+        for shard in records.chunks(17) {
+            let mut part = QualityAccumulator::new("sample", QualityEncoding::Phred33, 1);
+            let mut part_positions = PositionQualityAccumulator::default();
+            for record in shard {
+                part.add(record);
+                part_positions.add(&record.quality, QualityEncoding::Phred33);
+            }
+            merged.merge(part);
+            merged_positions.merge(part_positions);
+        }
+
+        assert_eq!(merged_positions.histograms, single_positions.histograms);
+        let profile = merged_positions.finish();
+        assert_eq!(profile.len(), 49);
+        assert_eq!(
+            serde_json::to_value(&profile).unwrap(),
+            serde_json::to_value(single_positions.finish()).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(merged.finish()).unwrap(),
+            serde_json::to_value(single.finish()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_position_percentiles() {
+        let mut positions = PositionQualityAccumulator::default();
+        // Cycle 1: Phred 10-19 in scrambled order; cycle 2: four reads, three above the Phred 60 cap
+        for (i, phred) in [14u8, 11, 19, 10, 17, 12, 16, 13, 18, 15].into_iter().enumerate() {
+            let mut quality = vec![phred + 33];
+            if let Some(&second) = [75u8, 2, 61, 70].get(i) {
+                quality.push(second + 33);
+            }
+            positions.add(&quality, QualityEncoding::Phred33);
+        }
+
+        let profile = positions.finish();
+        let first = &profile[0];
+        assert_eq!((first.position, first.reads), (1, 10));
+        assert_eq!(first.mean, 14.5);
+        assert_eq!(
+            [first.percentile_10, first.lower_quartile, first.median, first.upper_quartile, first.percentile_90],
+            [10.0, 12.0, 14.0, 17.0, 18.0]
+        );
+
+        let second = &profile[1];
+        assert_eq!(second.reads, 4);
+        assert_eq!(second.mean, 45.5);
+        assert_eq!(
+            [second.percentile_10, second.lower_quartile, second.median, second.upper_quartile, second.percentile_90],
+            [2.0, 2.0, 60.0, 60.0, 60.0]
+        );
+    }
+
+    #[test]
+    fn test_position_composition_percentages() {
+        let mut composition = PositionCompositionAccumulator::default();
+        for sequence in [&b"ACGT"[..], b"ACGN", b"aagt", b"ACR"] {
+            composition.add(sequence);
+        }
+
+        let profile = composition.finish();
+        assert_eq!(profile.len(), 4);
+        let percentages: Vec<[f64; 5]> = profile.iter().map(|p| [p.a, p.c, p.g, p.t, p.n]).collect();
+        assert_eq!(percentages[0], [100.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(percentages[1], [25.0, 75.0, 0.0, 0.0, 0.0]);
+        // R counts with N
+        assert_eq!(percentages[2], [0.0, 0.0, 75.0, 0.0, 25.0]);
+        // Only three reads reach cycle 4
+        assert_eq!(profile[3].position, 4);
+        assert!((profile[3].t - 200.0 / 3.0).abs() < 1e-9);
+        assert!((profile[3].n - 100.0 / 3.0).abs() < 1e-9);
+    }
+}
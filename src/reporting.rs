@@ -3,6 +3,7 @@
 //! Aggregates and reports QC metrics across multiple samples
 
 use crate::{contamination::ContaminationReport, QcStats, vlp::VlpReport};
+use crate::verdicts::{QcModule, Verdict};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -29,6 +30,21 @@ pub struct QcSummary {
     pub pass_rate: f64,
     pub average_quality: f64,
     pub average_gc_content: f64,
+    /// Mean % of reads remaining after exact deduplication
+    #[serde(default)]
+    pub average_percent_remaining_if_deduplicated: f64,
+    /// Samples per verdict for each module
+    #[serde(default)]
+    pub module_verdicts: Vec<ModuleVerdictCounts>,
+}
+
+/// How many samples got each verdict for one module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleVerdictCounts {
+    pub module: QcModule,
+    pub pass: usize,
+    pub warn: usize,
+    pub fail: usize,
 }
 
 /// QC reporter for multi-sample analysis
@@ -80,6 +96,16 @@ impl QcReporter {
             0.0
         };
 
+        let average_percent_remaining_if_deduplicated = if !sample_reports.is_empty() {
+            sample_reports
+                .iter()
+                .map(|r| r.quality_stats.duplication.percent_remaining_if_deduplicated)
+                .sum::<f64>()
+                / total_samples as f64
+        } else {
+            0.0
+        };
+
         let summary = QcSummary {
             total_samples,
             passed_samples,
@@ -87,6 +113,8 @@ impl QcReporter {
             pass_rate: (passed_samples as f64 / total_samples as f64) * 100.0,
             average_quality,
             average_gc_content,
+            average_percent_remaining_if_deduplicated,
+            module_verdicts: count_module_verdicts(&sample_reports),
         };
 
         MultiSampleReport {
@@ -108,4 +136,31 @@ impl QcReporter {
             && sample.contamination_report.phix_percentage <= self.contamination_threshold
             && sample.vlp_report.vlp_success_score >= 0.7
     }
-}
\ No newline at end of file
+}
+
+/// Tally module verdicts across samples (modules no sample was graded on are left out)
+fn count_module_verdicts(sample_reports: &[SampleQcReport]) -> Vec<ModuleVerdictCounts> {
+    QcModule::ALL
+        .iter()
+        .filter_map(|&module| {
+            let mut counts = ModuleVerdictCounts {
+                module,
+                pass: 0,
+                warn: 0,
+                fail: 0,
+            };
+            for verdict in sample_reports
+                .iter()
+                .flat_map(|r| &r.quality_stats.verdicts)
+                .filter(|v| v.module == module)
+            {
+                match verdict.verdict {
+                    Verdict::Pass => counts.pass += 1,
+                    Verdict::Warn => counts.warn += 1,
+                    Verdict::Fail => counts.fail += 1,
+                }
+            }
+            (counts.pass + counts.warn + counts.fail > 0).then_some(counts)
+        })
+        .collect()
+}
@@ -11,6 +11,8 @@
 //! - Memory-efficient streaming through massive Silva databases
 //! - 8-15× speedup on ARM platforms via NEON acceleration
 
+use crate::failed::FailedReadWriter;
+use crate::trimming::FailureReason;
 use crate::QcStatsMarker;
 use anyhow::Result;
 use biometal::alignment::{smith_waterman, ScoringMatrix};
@@ -267,11 +269,14 @@ impl RrnaRemover {
     }
 
     /// Process FASTQ file and remove rRNA sequences
+    ///
+    /// Removed reads are written to `failed_path` tagged `reason=rrna_match`.
     pub fn process_fastq<P: AsRef<Path>>(
         &self,
         input_path: P,
         database_path: P,
         output_path: Option<P>,
+        failed_path: Option<P>,
     ) -> Result<RrnaRemovalStats> {
         let sample_name = input_path
             .as_ref()
@@ -311,6 +316,7 @@ impl RrnaRemover {
         let fastq_stream = FastqStream::new(data_source)?;
 
         let mut non_rrna_records = Vec::new();
+        let mut failed_writer = failed_path.map(FailedReadWriter::create).transpose()?;
         let mut sample_sequences = Vec::new(); // For content analysis
 
         for record_result in fastq_stream {
@@ -336,12 +342,19 @@ impl RrnaRemover {
                 stats.rrna_reads_detected += 1;
                 stats.rrna_reads_removed += 1;
                 // Skip this record (remove it)
+                if let Some(ref mut failed_writer) = failed_writer {
+                    failed_writer.write_record(&record, FailureReason::RrnaMatch)?;
+                }
             } else {
                 // Keep non-rRNA reads
                 non_rrna_records.push(record);
             }
         }
 
+        if let Some(failed_writer) = failed_writer {
+            failed_writer.finish()?;
+        }
+
         // Stage 3: K-mer spectrum analysis for overall sample assessment
         println!("📊 Analyzing rRNA content with k-mer spectrum...");
         stats.rrna_content_score = self.assess_rrna_content(&sample_sequences);
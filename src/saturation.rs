@@ -0,0 +1,194 @@
+//! Library complexity and saturation curve estimation
+//!
+//! Works from a duplicate count histogram (how many distinct molecules were
+//! seen once, twice, ...), taken either from exact-sequence hashes of every
+//! read or from `biometal-pcr-dedup` cluster sizes:
+//! - Below the sequenced depth the curve is the expected number of distinct
+//!   reads in a random subsample (rarefaction)
+//! - Beyond it the curve follows the Chao-Jost extrapolation, which rises
+//!   towards the Chao1 library size estimate and is reliable to a few times
+//!   the current depth (preseq reports the same quantity with a rational-function fit)
+
+use crate::duplication::sequence_hash;
+use biometal::FastqRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Distinct molecules keyed by how many reads each one produced
+pub type DuplicateHistogram = BTreeMap<u64, u64>;
+
+/// Fraction of the estimated library reported by `reads_for_90_percent_of_library`
+const LIBRARY_FRACTION_TARGET: f64 = 0.9;
+
+/// One point of the complexity curve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaturationPoint {
+    pub relative_depth: f64, // 1.0 = the sequenced depth
+    pub reads: u64,
+    pub expected_distinct: f64,
+    pub extrapolated: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaturationEstimate {
+    pub total_reads: u64,
+    pub distinct_reads: u64,
+    pub singletons: u64,
+    pub doubletons: u64,
+    pub estimated_library_size: f64, // Chao1 estimate of distinct molecules
+    pub percent_of_library_observed: f64,
+    pub probability_next_read_new: f64, // Share of the next reads expected to be unseen (f1 / n)
+    pub reads_for_90_percent_of_library: Option<u64>, // None if the target is out of reach
+    pub curve: Vec<SaturationPoint>,
+}
+
+/// Streaming exact-duplicate counter over every read
+///
+/// Memory grows with the number of distinct sequences (one hash and count each).
+#[derive(Debug, Clone, Default)]
+pub struct ExactDuplicateCounter {
+    counts: HashMap<u64, u64>,
+}
+
+impl ExactDuplicateCounter {
+    /// Count one read by the hash of its full sequence
+    pub fn add(&mut self, record: &FastqRecord) {
+        if record.is_empty() {
+            return;
+        }
+        *self.counts.entry(sequence_hash(&record.sequence)).or_insert(0) += 1;
+    }
+
+    /// Fold in counts from another set of reads
+    pub fn merge(&mut self, other: Self) {
+        for (hash, count) in other.counts {
+            *self.counts.entry(hash).or_insert(0) += count;
+        }
+    }
+
+    /// Distinct sequences per duplicate count
+    pub fn finish(&self) -> DuplicateHistogram {
+        let mut histogram = DuplicateHistogram::new();
+        for &count in self.counts.values() {
+            *histogram.entry(count).or_insert(0) += 1;
+        }
+        histogram
+    }
+}
+
+/// Estimate library size and the complexity curve up to `max_relative_depth`
+/// times the sequenced depth
+pub fn estimate_saturation(histogram: &DuplicateHistogram, max_relative_depth: usize) -> SaturationEstimate {
+    let total_reads: u64 = histogram.iter().map(|(&count, &molecules)| count * molecules).sum();
+    if total_reads == 0 {
+        return SaturationEstimate::default();
+    }
+
+    let n = total_reads as f64;
+    let distinct_reads: u64 = histogram.values().sum();
+    let singletons = histogram.get(&1).copied().unwrap_or(0);
+    let doubletons = histogram.get(&2).copied().unwrap_or(0);
+    let f1 = singletons as f64;
+    let f2 = doubletons as f64;
+
+    // Chao1 estimate of molecules not yet seen (bias-corrected when f2 = 0)
+    let unseen = if doubletons > 0 {
+        (n - 1.0) / n * f1 * f1 / (2.0 * f2)
+    } else {
+        (n - 1.0) / n * f1 * (f1 - 1.0).max(0.0) / 2.0
+    };
+    let estimated_library_size = distinct_reads as f64 + unseen;
+
+    // Chance that one extra read hits an unseen molecule
+    let discovery_rate = if unseen > 0.0 { f1 / (n * unseen + f1) } else { 0.0 };
+    let extrapolate = |extra_reads: f64| distinct_reads as f64 + unseen * (1.0 - (1.0 - discovery_rate).powf(extra_reads));
+    let rarefy = |fraction: f64| {
+        histogram
+            .iter()
+            .map(|(&count, &molecules)| molecules as f64 * (1.0 - (1.0 - fraction).powi(count.min(i32::MAX as u64) as i32)))
+            .sum::<f64>()
+    };
+
+    let mut curve: Vec<SaturationPoint> = (1..=10)
+        .map(|tenth| {
+            let relative_depth = tenth as f64 / 10.0;
+            SaturationPoint {
+                relative_depth,
+                reads: (n * relative_depth).round() as u64,
+                expected_distinct: if tenth == 10 { distinct_reads as f64 } else { rarefy(relative_depth) },
+                extrapolated: false,
+            }
+        })
+        .collect();
+    curve.extend((2..=max_relative_depth).map(|fold| SaturationPoint {
+        relative_depth: fold as f64,
+        reads: total_reads * fold as u64,
+        expected_distinct: extrapolate(n * (fold - 1) as f64),
+        extrapolated: true,
+    }));
+
+    // Solve the extrapolation for the depth that reaches the target share of the library
+    let target = estimated_library_size * LIBRARY_FRACTION_TARGET;
+    let reads_for_90_percent_of_library = if distinct_reads as f64 >= target {
+        Some(total_reads)
+    } else if discovery_rate > 0.0 && discovery_rate < 1.0 {
+        let remaining = 1.0 - (target - distinct_reads as f64) / unseen;
+        let extra = remaining.ln() / (1.0 - discovery_rate).ln();
+        extra.is_finite().then(|| total_reads + extra.ceil() as u64)
+    } else {
+        None
+    };
+
+    SaturationEstimate {
+        total_reads,
+        distinct_reads,
+        singletons,
+        doubletons,
+        estimated_library_size,
+        percent_of_library_observed: distinct_reads as f64 / estimated_library_size * 100.0,
+        probability_next_read_new: f1 / n,
+        reads_for_90_percent_of_library,
+        curve,
+    }
+}
+
+/// Histogram from duplicate cluster sizes: reads outside any cluster count as
+/// molecules seen once
+pub fn histogram_from_clusters(total_reads: u64, cluster_sizes: &[usize]) -> DuplicateHistogram {
+    let mut histogram = DuplicateHistogram::new();
+    let clustered: u64 = cluster_sizes.iter().map(|&size| size as u64).sum();
+    for &size in cluster_sizes {
+        *histogram.entry(size as u64).or_insert(0) += 1;
+    }
+    let singletons = total_reads.saturating_sub(clustered);
+    if singletons > 0 {
+        *histogram.entry(1).or_insert(0) += singletons;
+    }
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve_rises_towards_chao1() {
+        // 100 reads: 40 molecules seen once, 20 twice, 4 five times
+        let histogram: DuplicateHistogram = [(1, 40), (2, 20), (5, 4)].into_iter().collect();
+        let estimate = estimate_saturation(&histogram, 5);
+
+        assert_eq!(estimate.total_reads, 100);
+        assert_eq!(estimate.distinct_reads, 64);
+        assert!((estimate.estimated_library_size - (64.0 + 0.99 * 40.0)).abs() < 1e-9);
+        assert!((estimate.probability_next_read_new - 0.4).abs() < 1e-12);
+
+        let distinct: Vec<f64> = estimate.curve.iter().map(|p| p.expected_distinct).collect();
+        assert!(distinct.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(estimate.curve[9].expected_distinct, 64.0);
+        assert!(*distinct.last().unwrap() < estimate.estimated_library_size);
+        assert!(estimate.reads_for_90_percent_of_library.unwrap() > 100);
+
+        let from_clusters = histogram_from_clusters(10, &[3, 2]);
+        assert_eq!(from_clusters, [(1, 5), (2, 1), (3, 1)].into_iter().collect());
+    }
+}
@@ -0,0 +1,504 @@
+//! Per-tile quality analysis
+//!
+//! FastQC-style "per tile sequence quality" using biometal spatial primitives:
+//! - parse_illumina_coordinates: lane/tile from Illumina read headers
+//! - Tile × cycle mean-quality matrix per lane
+//! - Outlier tiles flagged against the lane median (bubbles, flowcell damage)
+//! - TileFilter: removes reads from outlier or explicitly listed tiles
+
+use crate::encoding::QualityEncoding;
+use crate::QcStatsMarker;
+use anyhow::Result;
+use biometal::io::{DataSource, FastqStream, FastqWriter};
+use biometal::operations::spatial::parse_illumina_coordinates;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Default drop of a tile's mean quality below the lane median (Phred units)
+pub const DEFAULT_MAX_MEAN_DROP: f64 = 2.0;
+
+/// Default drop at any single cycle below the lane median (Phred units)
+pub const DEFAULT_MAX_CYCLE_DROP: f64 = 5.0;
+
+/// Rule deciding when a tile is an outlier relative to the other tiles in its lane
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TileOutlierRule {
+    pub max_mean_drop: f64,
+    pub max_cycle_drop: f64,
+}
+
+impl Default for TileOutlierRule {
+    fn default() -> Self {
+        Self {
+            max_mean_drop: DEFAULT_MAX_MEAN_DROP,
+            max_cycle_drop: DEFAULT_MAX_CYCLE_DROP,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileQuality {
+    pub lane: u32,
+    pub tile: u32,
+    pub reads: u64,
+    pub mean_quality: f64,
+    pub deviation_from_lane_median: f64, // Negative = worse than the lane
+    pub worst_cycle: usize,              // 1-based cycle with the largest drop
+    pub worst_cycle_deviation: f64,
+    pub per_cycle_mean: Vec<f64>,
+    pub flagged: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaneTileSummary {
+    pub lane: u32,
+    pub tiles: usize,
+    pub median_tile_quality: f64,
+    pub flagged_tiles: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TileQualityReport {
+    pub reads_with_coordinates: u64,
+    pub reads_without_coordinates: u64,
+    pub rule: TileOutlierRule,
+    pub lanes: Vec<LaneTileSummary>,
+    pub tiles: Vec<TileQuality>,
+}
+
+impl TileQualityReport {
+    /// Flagged (lane, tile) pairs across all lanes
+    pub fn flagged_tiles(&self) -> Vec<(u32, u32)> {
+        self.tiles
+            .iter()
+            .filter(|t| t.flagged)
+            .map(|t| (t.lane, t.tile))
+            .collect()
+    }
+}
+
+/// Quality sums for one tile
+#[derive(Debug, Clone, Default)]
+struct TileCounts {
+    reads: u64,
+    quality_sum: u64,
+    bases: u64,
+    cycle_sums: Vec<u64>,
+    cycle_bases: Vec<u64>,
+}
+
+impl TileCounts {
+    fn cycle_means(&self) -> Vec<f64> {
+        self.cycle_sums
+            .iter()
+            .zip(&self.cycle_bases)
+            .map(|(&sum, &bases)| if bases > 0 { sum as f64 / bases as f64 } else { 0.0 })
+            .collect()
+    }
+}
+
+/// Accumulates the tile × cycle quality matrix from read headers
+#[derive(Debug, Clone, Default)]
+pub struct TileQualityAccumulator {
+    pub rule: TileOutlierRule,
+    tiles: BTreeMap<(u32, u32), TileCounts>,
+    reads_without_coordinates: u64,
+}
+
+impl TileQualityAccumulator {
+    pub fn new(rule: TileOutlierRule) -> Self {
+        Self {
+            rule,
+            ..Default::default()
+        }
+    }
+
+    /// Add one read; reads without Illumina coordinates are counted but not profiled
+    pub fn add(&mut self, read_id: &str, quality: &[u8], encoding: QualityEncoding) {
+        let coordinate = match parse_illumina_coordinates(read_id) {
+            Ok(coordinate) => coordinate,
+            Err(_) => {
+                self.reads_without_coordinates += 1;
+                return;
+            }
+        };
+
+        let counts = self
+            .tiles
+            .entry((coordinate.lane.into(), coordinate.tile))
+            .or_default();
+
+        if quality.len() > counts.cycle_sums.len() {
+            counts.cycle_sums.resize(quality.len(), 0);
+            counts.cycle_bases.resize(quality.len(), 0);
+        }
+
+        counts.reads += 1;
+        for (cycle, &q) in quality.iter().enumerate() {
+            let phred = encoding.phred(q) as u64;
+            counts.quality_sum += phred;
+            counts.bases += 1;
+            counts.cycle_sums[cycle] += phred;
+            counts.cycle_bases[cycle] += 1;
+        }
+    }
+
+    /// Fold in tile counts from another set of reads
+    pub fn merge(&mut self, other: Self) {
+        self.reads_without_coordinates += other.reads_without_coordinates;
+
+        for (key, other) in other.tiles {
+            let counts = self.tiles.entry(key).or_default();
+            if other.cycle_sums.len() > counts.cycle_sums.len() {
+                counts.cycle_sums.resize(other.cycle_sums.len(), 0);
+                counts.cycle_bases.resize(other.cycle_bases.len(), 0);
+            }
+
+            counts.reads += other.reads;
+            counts.quality_sum += other.quality_sum;
+            counts.bases += other.bases;
+            for (cycle, (&sum, &bases)) in other.cycle_sums.iter().zip(&other.cycle_bases).enumerate() {
+                counts.cycle_sums[cycle] += sum;
+                counts.cycle_bases[cycle] += bases;
+            }
+        }
+    }
+
+    /// Compare every tile against its lane median and flag outliers
+    pub fn finish(&self) -> TileQualityReport {
+        let mut report = TileQualityReport {
+            reads_with_coordinates: self.tiles.values().map(|c| c.reads).sum(),
+            reads_without_coordinates: self.reads_without_coordinates,
+            rule: self.rule,
+            ..Default::default()
+        };
+
+        let mut lanes: BTreeMap<u32, Vec<(u32, &TileCounts)>> = BTreeMap::new();
+        for (&(lane, tile), counts) in &self.tiles {
+            lanes.entry(lane).or_default().push((tile, counts));
+        }
+
+        for (lane, tiles) in lanes {
+            let tile_means: Vec<f64> = tiles
+                .iter()
+                .map(|(_, c)| if c.bases > 0 { c.quality_sum as f64 / c.bases as f64 } else { 0.0 })
+                .collect();
+            let cycle_means: Vec<Vec<f64>> = tiles.iter().map(|(_, c)| c.cycle_means()).collect();
+
+            let lane_median = median(&tile_means);
+            let max_cycles = cycle_means.iter().map(|m| m.len()).max().unwrap_or(0);
+            let lane_cycle_medians: Vec<f64> = (0..max_cycles)
+                .map(|cycle| {
+                    let values: Vec<f64> = cycle_means.iter().filter_map(|m| m.get(cycle).copied()).collect();
+                    median(&values)
+                })
+                .collect();
+
+            // A single tile has nothing to be compared against
+            let comparable = tiles.len() > 1;
+            let mut flagged_tiles = Vec::new();
+
+            for (i, (tile, counts)) in tiles.iter().enumerate() {
+                let deviation = tile_means[i] - lane_median;
+                let (worst_cycle, worst_cycle_deviation) = cycle_means[i]
+                    .iter()
+                    .zip(&lane_cycle_medians)
+                    .map(|(mean, lane_cycle)| mean - lane_cycle)
+                    .enumerate()
+                    .fold((0, 0.0f64), |worst, (cycle, d)| if d < worst.1 { (cycle + 1, d) } else { worst });
+
+                let flagged = comparable
+                    && (deviation < -self.rule.max_mean_drop || worst_cycle_deviation < -self.rule.max_cycle_drop);
+                if flagged {
+                    flagged_tiles.push(*tile);
+                }
+
+                report.tiles.push(TileQuality {
+                    lane,
+                    tile: *tile,
+                    reads: counts.reads,
+                    mean_quality: tile_means[i],
+                    deviation_from_lane_median: deviation,
+                    worst_cycle,
+                    worst_cycle_deviation,
+                    per_cycle_mean: cycle_means[i].clone(),
+                    flagged,
+                });
+            }
+
+            report.lanes.push(LaneTileSummary {
+                lane,
+                tiles: tiles.len(),
+                median_tile_quality: lane_median,
+                flagged_tiles,
+            });
+        }
+
+        report
+    }
+}
+
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        sorted[mid]
+    } else {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    }
+}
+
+/// A tile to exclude: `1101` matches the tile in every lane, `2:1101` only in lane 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileSelector {
+    pub lane: Option<u32>,
+    pub tile: u32,
+}
+
+impl FromStr for TileSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        match s.split_once(':') {
+            Some((lane, tile)) => Ok(Self {
+                lane: Some(lane.parse()?),
+                tile: tile.parse()?,
+            }),
+            None => Ok(Self {
+                lane: None,
+                tile: s.parse()?,
+            }),
+        }
+    }
+}
+
+/// Parse a comma-separated tile list such as `1101,1102,2:2204`
+pub fn parse_tile_list(list: &str) -> Result<Vec<TileSelector>> {
+    list.split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            entry
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid tile '{}' (expected TILE or LANE:TILE)", entry))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludedTile {
+    pub lane: Option<u32>, // None = explicit tile matched in every lane
+    pub tile: u32,
+    pub source: String,    // "outlier" or "explicit"
+    pub reads_removed: u64,
+}
+
+impl ExcludedTile {
+    pub fn matches(&self, lane: u32, tile: u32) -> bool {
+        self.tile == tile && self.lane.is_none_or(|l| l == lane)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TileFilterStats {
+    pub sample_name: String,
+    pub total_reads: u64,
+    pub reads_kept: u64,
+    pub reads_removed: u64,
+    pub removal_rate: f64,
+    pub reads_without_coordinates: u64, // Always kept
+    pub outlier_rule: Option<TileOutlierRule>,
+    pub excluded_tiles: Vec<ExcludedTile>,
+    pub phred_offset: u8,
+}
+
+impl QcStatsMarker for TileFilterStats {}
+
+/// Removes every read from outlier tiles and/or an explicit tile list
+pub struct TileFilter {
+    pub outlier_rule: Option<TileOutlierRule>, // None = only use the explicit list
+    pub explicit_tiles: Vec<TileSelector>,
+    pub encoding: QualityEncoding,
+}
+
+impl TileFilter {
+    pub fn new(
+        outlier_rule: Option<TileOutlierRule>,
+        explicit_tiles: Vec<TileSelector>,
+        encoding: QualityEncoding,
+    ) -> Self {
+        Self {
+            outlier_rule,
+            explicit_tiles,
+            encoding,
+        }
+    }
+
+    /// Profile tiles (if outlier detection is on), then stream reads from kept tiles
+    pub fn filter_reads<P: AsRef<Path>>(&self, input_path: P, output_path: P) -> Result<TileFilterStats> {
+        let sample_name = input_path
+            .as_ref()
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        // Pass 1: per-tile quality to find outliers
+        let mut flagged_tiles = Vec::new();
+        if let Some(rule) = self.outlier_rule {
+            let mut accumulator = TileQualityAccumulator::new(rule);
+            let fastq_stream = FastqStream::new(DataSource::from_path(&input_path))?;
+            for record_result in fastq_stream {
+                let record = record_result?;
+                accumulator.add(&record.id, &record.quality, self.encoding);
+            }
+            flagged_tiles = accumulator.finish().flagged_tiles();
+        }
+        let mut excluded_tiles = self.excluded_tiles(&flagged_tiles);
+
+        // Pass 2: write reads that are not on an excluded tile
+        let mut stats = TileFilterStats {
+            sample_name,
+            outlier_rule: self.outlier_rule,
+            phred_offset: self.encoding.offset(),
+            ..Default::default()
+        };
+
+        let fastq_stream = FastqStream::new(DataSource::from_path(&input_path))?;
+        let mut writer = FastqWriter::create(&output_path)?;
+
+        for record_result in fastq_stream {
+            let record = record_result?;
+            stats.total_reads += 1;
+
+            let excluded = match parse_illumina_coordinates(&record.id) {
+                Ok(coordinate) => {
+                    let lane: u32 = coordinate.lane.into();
+                    excluded_tiles
+                        .iter_mut()
+                        .find(|excluded| excluded.matches(lane, coordinate.tile))
+                }
+                Err(_) => {
+                    stats.reads_without_coordinates += 1;
+                    None
+                }
+            };
+
+            match excluded {
+                Some(excluded) => {
+                    excluded.reads_removed += 1;
+                    stats.reads_removed += 1;
+                }
+                None => {
+                    writer.write_record(&record)?;
+                    stats.reads_kept += 1;
+                }
+            }
+        }
+
+        writer.finish()?;
+
+        stats.removal_rate = if stats.total_reads > 0 {
+            (stats.reads_removed as f64 / stats.total_reads as f64) * 100.0
+        } else {
+            0.0
+        };
+        stats.excluded_tiles = excluded_tiles;
+
+        Ok(stats)
+    }
+
+    /// Outlier tiles followed by the explicit list, each lane/tile listed once
+    fn excluded_tiles(&self, flagged_tiles: &[(u32, u32)]) -> Vec<ExcludedTile> {
+        let outliers = flagged_tiles.iter().map(|&(lane, tile)| (Some(lane), tile, "outlier"));
+        let explicit = self.explicit_tiles.iter().map(|selector| (selector.lane, selector.tile, "explicit"));
+
+        let mut excluded_tiles: Vec<ExcludedTile> = Vec::new();
+        for (lane, tile, source) in outliers.chain(explicit) {
+            // A listed tile that is also an outlier keeps its outlier entry
+            if excluded_tiles.iter().any(|excluded| excluded.lane == lane && excluded.tile == tile) {
+                continue;
+            }
+            excluded_tiles.push(ExcludedTile {
+                lane,
+                tile,
+                source: source.to_string(),
+                reads_removed: 0,
+            });
+        }
+        excluded_tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_tile(accumulator: &mut TileQualityAccumulator, lane: u32, tile: u32, cycles: &[u8]) {
+        let quality: Vec<u8> = cycles.iter().map(|&phred| phred + 33).collect();
+        for read in 0..10 {
+            let id = format!("A00123:8:HXXXXX:{}:{}:{}:1000 1:N:0:1", lane, tile, read);
+            accumulator.add(&id, &quality, QualityEncoding::Phred33);
+        }
+    }
+
+    #[test]
+    fn test_outlier_tiles_against_lane_median() {
+        let mut accumulator = TileQualityAccumulator::default();
+        for tile in 1101..=1105 {
+            add_tile(&mut accumulator, 1, tile, &[35; 5]);
+        }
+        add_tile(&mut accumulator, 1, 2101, &[33; 5]); // Mean 2 below the median: not over the limit
+        add_tile(&mut accumulator, 1, 2102, &[32; 5]); // Mean 3 below
+        add_tile(&mut accumulator, 1, 2103, &[35, 35, 35, 35, 29]); // Mean 1.2 below, cycle 5 down by 6
+        add_tile(&mut accumulator, 1, 2104, &[35, 35, 35, 35, 31]); // Cycle 5 down by 4
+        add_tile(&mut accumulator, 2, 1101, &[10; 5]); // Alone in its lane
+        accumulator.add("read_without_coordinates", b"IIIII", QualityEncoding::Phred33);
+
+        let report = accumulator.finish();
+        assert_eq!(report.reads_with_coordinates, 100);
+        assert_eq!(report.reads_without_coordinates, 1);
+        assert_eq!(report.lanes[0].median_tile_quality, 35.0);
+        assert_eq!(report.flagged_tiles(), vec![(1, 2102), (1, 2103)]);
+
+        let tile = report.tiles.iter().find(|t| t.tile == 2103).unwrap();
+        assert_eq!((tile.worst_cycle, tile.worst_cycle_deviation), (5, -6.0));
+    }
+
+    #[test]
+    fn test_tile_selectors() {
+        assert_eq!("1101".parse::<TileSelector>().unwrap(), TileSelector { lane: None, tile: 1101 });
+        assert_eq!(
+            parse_tile_list(" 1101, 2:2204,").unwrap(),
+            vec![TileSelector { lane: None, tile: 1101 }, TileSelector { lane: Some(2), tile: 2204 }]
+        );
+        for bad in ["x", "2:", ":1101", "1:2:3", "-1", "1101;1102"] {
+            assert!(parse_tile_list(bad).is_err(), "{}", bad);
+        }
+
+        let any_lane = ExcludedTile {
+            lane: None,
+            tile: 1101,
+            source: "explicit".to_string(),
+            reads_removed: 0,
+        };
+        assert!(any_lane.matches(1, 1101) && any_lane.matches(2, 1101));
+        assert!(!ExcludedTile { lane: Some(2), ..any_lane }.matches(1, 1101));
+
+        // Listed tiles that are also outliers, or listed twice, appear once
+        let filter = TileFilter::new(None, parse_tile_list("1:1101,1102,1:1101,1102").unwrap(), QualityEncoding::Phred33);
+        let excluded = filter.excluded_tiles(&[(1, 1101), (2, 1102)]);
+        let listed: Vec<_> = excluded.iter().map(|e| (e.lane, e.tile, e.source.as_str())).collect();
+        assert_eq!(
+            listed,
+            vec![(Some(1), 1101, "outlier"), (Some(2), 1102, "outlier"), (None, 1102, "explicit")]
+        );
+    }
+}
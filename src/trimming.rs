@@ -0,0 +1,461 @@
+//! Quality trimming for biometal-quality-filter
+//!
+//! Steps, applied in this order:
+//! - Modified Mott (BWA / cutadapt `-q`): cut where the running sum of
+//!   `cutoff - quality` from each end peaks, matching cutadapt exactly
+//! - LEADING: drop bases below a quality from the 5' end
+//! - TRAILING: drop bases below a quality from the 3' end
+//! - SLIDINGWINDOW: scan from the 5' end and cut where the mean quality of a
+//!   window first drops below the threshold (high-quality bases at the start
+//!   of that window are kept, as Trimmomatic does)
+//! - N ends: drop ambiguous bases left at either end (cutadapt `--trim-n`)
+//!
+//! Steps return the range of the read to keep; the caller applies the minimum
+//! length and read-level filters to what is left.
+//!
+//! `LengthRules` are shared by the filter and the trimmers: HEADCROP and CROP
+//! cut the read as it arrives, before any other trimming, and the minimum and
+//! maximum lengths are checked on the final read.
+
+use crate::encoding::QualityEncoding;
+use anyhow::{Context, Result};
+use biometal::FastqRecord;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::str::FromStr;
+
+/// A trimming step, used to attribute removed bases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrimStep {
+    HeadCrop,
+    Crop,
+    Mott,
+    Leading,
+    Trailing,
+    SlidingWindow,
+    NEnds,
+}
+
+impl TrimStep {
+    /// Human-readable step name
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrimStep::HeadCrop => "Head crop",
+            TrimStep::Crop => "Crop",
+            TrimStep::Mott => "Mott",
+            TrimStep::Leading => "Leading",
+            TrimStep::Trailing => "Trailing",
+            TrimStep::SlidingWindow => "Sliding window",
+            TrimStep::NEnds => "N ends",
+        }
+    }
+}
+
+/// Why a read was not written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureReason {
+    TooShort,
+    TooLong,
+    TooManyNs,
+    LowComplexity,
+    TooManyExpectedErrors,
+    LowMeanQuality,
+    RrnaMatch,
+    HostMatch,
+}
+
+impl FailureReason {
+    /// Human-readable reason
+    pub fn label(&self) -> &'static str {
+        match self {
+            FailureReason::TooShort => "Too short after trimming",
+            FailureReason::TooLong => "Longer than maximum length",
+            FailureReason::TooManyNs => "Too many Ns",
+            FailureReason::LowComplexity => "Low complexity",
+            FailureReason::TooManyExpectedErrors => "Expected errors above maximum",
+            FailureReason::LowMeanQuality => "Mean quality below threshold",
+            FailureReason::RrnaMatch => "rRNA match",
+            FailureReason::HostMatch => "Host match",
+        }
+    }
+
+    /// Tag written to rejected-read headers (`reason=<tag>`), same as the JSON key
+    pub fn tag(&self) -> &'static str {
+        match self {
+            FailureReason::TooShort => "too_short",
+            FailureReason::TooLong => "too_long",
+            FailureReason::TooManyNs => "too_many_ns",
+            FailureReason::LowComplexity => "low_complexity",
+            FailureReason::TooManyExpectedErrors => "too_many_expected_errors",
+            FailureReason::LowMeanQuality => "low_mean_quality",
+            FailureReason::RrnaMatch => "rrna_match",
+            FailureReason::HostMatch => "host_match",
+        }
+    }
+}
+
+/// Fixed crops and read length limits
+#[derive(Debug, Clone, Default)]
+pub struct LengthRules {
+    pub headcrop: usize,           // Bases removed from the 5' end (Trimmomatic HEADCROP)
+    pub crop: Option<usize>,       // Then keep at most this many bases (Trimmomatic CROP)
+    pub min_length: usize,         // Shorter final reads are discarded
+    pub max_length: Option<usize>, // Longer final reads are discarded
+}
+
+/// Reads affected by each length rule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LengthRuleStats {
+    pub reads_headcropped: u64,
+    pub reads_cropped: u64,
+    pub reads_too_short: u64,
+    pub reads_too_long: u64,
+}
+
+impl LengthRules {
+    /// Whether HEADCROP or CROP is configured
+    pub fn crops(&self) -> bool {
+        self.headcrop > 0 || self.crop.is_some()
+    }
+
+    /// Part of a read of `length` bases kept by HEADCROP and CROP
+    pub fn crop_range(&self, length: usize, counts: &mut LengthRuleStats) -> Range<usize> {
+        let start = self.headcrop.min(length);
+        let end = self.crop.map_or(length, |crop| length.min(start + crop));
+        if start > 0 {
+            counts.reads_headcropped += 1;
+        }
+        if end < length {
+            counts.reads_cropped += 1;
+        }
+        start..end
+    }
+
+    /// Apply HEADCROP and CROP to a whole record
+    pub fn crop_record(&self, record: FastqRecord, counts: &mut LengthRuleStats) -> FastqRecord {
+        if !self.crops() {
+            return record;
+        }
+        let keep = self.crop_range(record.sequence.len(), counts);
+        FastqRecord::new(
+            record.id,
+            record.sequence[keep.clone()].to_vec(),
+            record.quality[keep].to_vec(),
+        )
+    }
+
+    /// Check a final read length against the limits
+    pub fn check(&self, length: usize, counts: &mut LengthRuleStats) -> Option<FailureReason> {
+        if length < self.min_length {
+            counts.reads_too_short += 1;
+            Some(FailureReason::TooShort)
+        } else if self.max_length.is_some_and(|max| length > max) {
+            counts.reads_too_long += 1;
+            Some(FailureReason::TooLong)
+        } else {
+            None
+        }
+    }
+}
+
+/// SLIDINGWINDOW parameters, written `<size>:<quality>` on the command line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlidingWindow {
+    pub window_size: usize,
+    pub required_quality: f64,
+}
+
+impl FromStr for SlidingWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (size, quality) = s
+            .split_once(':')
+            .with_context(|| format!("Sliding window must be <size>:<quality>, got '{}'", s))?;
+        let window_size: usize = size.parse().with_context(|| format!("Invalid window size: {}", size))?;
+        if window_size == 0 {
+            anyhow::bail!("Sliding window size must be at least 1");
+        }
+        let required_quality: f64 = quality
+            .parse()
+            .with_context(|| format!("Invalid window quality: {}", quality))?;
+        Ok(Self {
+            window_size,
+            required_quality,
+        })
+    }
+}
+
+/// Modified-Mott cutoffs, written like cutadapt `-q`: `<3' cutoff>` or `<5' cutoff>,<3' cutoff>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MottCutoffs {
+    pub five_prime: u8, // 0 disables 5' trimming
+    pub three_prime: u8,
+}
+
+impl FromStr for MottCutoffs {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = |value: &str| -> Result<u8> {
+            value
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid Mott quality cutoff: {}", value))
+        };
+        match s.split_once(',') {
+            Some((five_prime, three_prime)) => Ok(Self {
+                five_prime: parse(five_prime)?,
+                three_prime: parse(three_prime)?,
+            }),
+            None => Ok(Self {
+                five_prime: 0,
+                three_prime: parse(s)?,
+            }),
+        }
+    }
+}
+
+/// Bases kept after trimming and how many each step removed
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrimOutcome {
+    pub keep: Range<usize>,
+    pub removed: Vec<(TrimStep, usize)>, // Steps that removed at least one base
+}
+
+/// Per-base quality trimmer
+#[derive(Debug, Clone)]
+pub struct QualityTrimmer {
+    pub encoding: QualityEncoding,
+    pub mott: Option<MottCutoffs>,
+    pub leading: Option<u8>,
+    pub trailing: Option<u8>,
+    pub sliding_window: Option<SlidingWindow>,
+    pub trim_ns: bool,
+}
+
+impl QualityTrimmer {
+    pub fn new(encoding: QualityEncoding) -> Self {
+        Self {
+            encoding,
+            mott: None,
+            leading: None,
+            trailing: None,
+            sliding_window: None,
+            trim_ns: false,
+        }
+    }
+
+    /// Whether any trimming step is configured
+    pub fn is_enabled(&self) -> bool {
+        self.mott.is_some() || self.leading.is_some() || self.trailing.is_some() || self.sliding_window.is_some() || self.trim_ns
+    }
+
+    /// Work out which part of a read to keep
+    pub fn trim(&self, sequence: &[u8], quality: &[u8]) -> TrimOutcome {
+        let phred: Vec<u8> = quality.iter().map(|&q| self.encoding.phred(q)).collect();
+        let mut keep = 0..phred.len();
+        let mut removed = Vec::new();
+        let mut record = |step: TrimStep, before: usize, after: usize| {
+            if before > after {
+                removed.push((step, before - after));
+            }
+        };
+
+        if let Some(cutoffs) = self.mott {
+            let before = keep.len();
+            keep = mott_range(&phred, cutoffs);
+            record(TrimStep::Mott, before, keep.len());
+        }
+
+        if let Some(threshold) = self.leading {
+            let before = keep.len();
+            while keep.start < keep.end && phred[keep.start] < threshold {
+                keep.start += 1;
+            }
+            record(TrimStep::Leading, before, keep.len());
+        }
+
+        if let Some(threshold) = self.trailing {
+            let before = keep.len();
+            while keep.end > keep.start && phred[keep.end - 1] < threshold {
+                keep.end -= 1;
+            }
+            record(TrimStep::Trailing, before, keep.len());
+        }
+
+        if let Some(window) = self.sliding_window {
+            let before = keep.len();
+            keep.end = keep.start + sliding_window_length(&phred[keep.clone()], window);
+            record(TrimStep::SlidingWindow, before, keep.len());
+        }
+
+        if self.trim_ns {
+            let before = keep.len();
+            while keep.start < keep.end && is_ambiguous(sequence[keep.start]) {
+                keep.start += 1;
+            }
+            while keep.end > keep.start && is_ambiguous(sequence[keep.end - 1]) {
+                keep.end -= 1;
+            }
+            record(TrimStep::NEnds, before, keep.len());
+        }
+
+        TrimOutcome { keep, removed }
+    }
+}
+
+/// N or any other base that is not A, C, G or T
+pub fn is_ambiguous(base: u8) -> bool {
+    !matches!(base, b'A' | b'C' | b'G' | b'T' | b'a' | b'c' | b'g' | b't')
+}
+
+/// Number of ambiguous bases in a sequence
+pub fn count_ambiguous(sequence: &[u8]) -> usize {
+    sequence.iter().filter(|&&base| is_ambiguous(base)).count()
+}
+
+/// Range kept by the modified Mott algorithm (cutadapt's `quality_trim_index`)
+fn mott_range(phred: &[u8], cutoffs: MottCutoffs) -> Range<usize> {
+    // 5' end: keep after the position where the running sum peaks
+    let mut start = 0;
+    let (mut sum, mut max_sum) = (0i64, 0i64);
+    for (i, &q) in phred.iter().enumerate() {
+        sum += cutoffs.five_prime as i64 - q as i64;
+        if sum < 0 {
+            break;
+        }
+        if sum > max_sum {
+            max_sum = sum;
+            start = i + 1;
+        }
+    }
+
+    // 3' end: the same scan from the other side
+    let mut stop = phred.len();
+    let (mut sum, mut max_sum) = (0i64, 0i64);
+    for (i, &q) in phred.iter().enumerate().rev() {
+        sum += cutoffs.three_prime as i64 - q as i64;
+        if sum < 0 {
+            break;
+        }
+        if sum > max_sum {
+            max_sum = sum;
+            stop = i;
+        }
+    }
+
+    if start >= stop {
+        0..0
+    } else {
+        start..stop
+    }
+}
+
+/// Bases to keep from the 5' end under SLIDINGWINDOW
+fn sliding_window_length(phred: &[u8], window: SlidingWindow) -> usize {
+    // Reads shorter than the window are judged as a single window
+    let size = window.window_size.min(phred.len());
+    if size == 0 {
+        return 0;
+    }
+
+    let required_total = window.required_quality * size as f64;
+    let mut total: f64 = phred[..size].iter().map(|&q| q as f64).sum();
+
+    for start in 0..=phred.len() - size {
+        if start > 0 {
+            total += phred[start + size - 1] as f64 - phred[start - 1] as f64;
+        }
+        if total < required_total {
+            // Keep the good bases at the head of the failing window
+            let good = phred[start..start + size]
+                .iter()
+                .take_while(|&&q| q as f64 >= window.required_quality)
+                .count();
+            return start + good;
+        }
+    }
+
+    phred.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qualities(phred: &[u8]) -> Vec<u8> {
+        phred.iter().map(|&q| q + 33).collect()
+    }
+
+    fn trim(trimmer: &QualityTrimmer, phred: &[u8]) -> TrimOutcome {
+        trimmer.trim(&vec![b'A'; phred.len()], &qualities(phred))
+    }
+
+    #[test]
+    fn test_leading_trailing_and_window() {
+        let mut trimmer = QualityTrimmer::new(QualityEncoding::Phred33);
+        trimmer.leading = Some(3);
+        trimmer.trailing = Some(3);
+        trimmer.sliding_window = Some("4:15".parse().unwrap());
+
+        // 2 low bases, 10 good, then a run that drags the window below 15, then low tail
+        let outcome = trim(&trimmer, &[2, 2, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 20, 5, 5, 5, 30, 30, 2]);
+
+        assert_eq!(outcome.keep, 2..13);
+        assert_eq!(
+            outcome.removed,
+            vec![(TrimStep::Leading, 2), (TrimStep::Trailing, 1), (TrimStep::SlidingWindow, 5)]
+        );
+
+        assert_eq!(trim(&trimmer, &[2, 2]).keep.len(), 0);
+        assert!("4".parse::<SlidingWindow>().is_err());
+    }
+
+    #[test]
+    fn test_mott_matches_cutadapt() {
+        // Example from the cutadapt documentation: -q 10 cuts after the 27
+        let mut trimmer = QualityTrimmer::new(QualityEncoding::Phred33);
+        trimmer.mott = Some("10".parse().unwrap());
+        assert_eq!(trim(&trimmer, &[42, 40, 26, 27, 8, 7, 11, 4, 2, 3]).keep, 0..4);
+
+        trimmer.mott = Some("10,10".parse().unwrap());
+        assert_eq!(trim(&trimmer, &[5, 8, 42, 40, 26, 27, 8, 7, 11, 4, 2, 3]).keep, 2..6);
+        assert_eq!(trim(&trimmer, &[2, 2, 2]).keep, 0..0);
+    }
+
+    #[test]
+    fn test_length_rules() {
+        let rules = LengthRules {
+            headcrop: 10,
+            crop: Some(50),
+            min_length: 20,
+            max_length: Some(40),
+        };
+        let mut counts = LengthRuleStats::default();
+
+        assert_eq!(rules.crop_range(100, &mut counts), 10..60);
+        assert_eq!(rules.crop_range(30, &mut counts), 10..30);
+        assert_eq!(rules.crop_range(5, &mut counts), 5..5);
+        assert_eq!(rules.check(19, &mut counts), Some(FailureReason::TooShort));
+        assert_eq!(rules.check(41, &mut counts), Some(FailureReason::TooLong));
+        assert_eq!(rules.check(40, &mut counts), None);
+        assert_eq!(
+            (counts.reads_headcropped, counts.reads_cropped, counts.reads_too_short, counts.reads_too_long),
+            (3, 1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_n_ends() {
+        let mut trimmer = QualityTrimmer::new(QualityEncoding::Phred33);
+        trimmer.trim_ns = true;
+
+        let outcome = trimmer.trim(b"NNACGNTN", &qualities(&[30; 8]));
+        assert_eq!(outcome.keep, 2..7);
+        assert_eq!(outcome.removed, vec![(TrimStep::NEnds, 3)]);
+        assert_eq!(count_ambiguous(b"NNACGNTR"), 4);
+    }
+}
@@ -0,0 +1,372 @@
+//! FastQC-style PASS/WARN/FAIL module verdicts
+//!
+//! Thresholds come from a limits file in FastQC's `limits.txt` format
+//! (`<key> <warn|error|ignore> <value>` per line, `#` comments), so an
+//! existing FastQC configuration can be reused as is. Keys this tool has no
+//! module for (`kmer`, `tile`, `sequence_length`, ...) are accepted and ignored.
+//! Defaults match the thresholds FastQC ships with.
+
+use crate::QcStats;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Verdict {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "PASS"),
+            Verdict::Warn => write!(f, "WARN"),
+            Verdict::Fail => write!(f, "FAIL"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QcModule {
+    PerBaseQuality,
+    NContent,
+    GcContent,
+    Duplication,
+    AdapterContent,
+    Overrepresented,
+}
+
+impl QcModule {
+    /// Modules in report order
+    pub const ALL: [QcModule; 6] = [
+        QcModule::PerBaseQuality,
+        QcModule::NContent,
+        QcModule::GcContent,
+        QcModule::Duplication,
+        QcModule::AdapterContent,
+        QcModule::Overrepresented,
+    ];
+
+    /// Human-readable module name
+    pub fn title(&self) -> &'static str {
+        match self {
+            QcModule::PerBaseQuality => "Per base sequence quality",
+            QcModule::NContent => "Per base N content",
+            QcModule::GcContent => "Per sequence GC content",
+            QcModule::Duplication => "Sequence duplication levels",
+            QcModule::AdapterContent => "Adapter content",
+            QcModule::Overrepresented => "Overrepresented sequences",
+        }
+    }
+
+    /// Key used for `ignore` lines in the limits file
+    fn limits_key(&self) -> &'static str {
+        match self {
+            QcModule::PerBaseQuality => "quality_base",
+            QcModule::NContent => "n_content",
+            QcModule::GcContent => "gc_sequence",
+            QcModule::Duplication => "duplication",
+            QcModule::AdapterContent => "adapter",
+            QcModule::Overrepresented => "overrepresented",
+        }
+    }
+}
+
+/// Verdict for one module with the value that decided it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleVerdict {
+    pub module: QcModule,
+    pub verdict: Verdict,
+    pub detail: String,
+}
+
+/// Warn and error thresholds for one metric
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub warn: f64,
+    pub error: f64,
+}
+
+impl Thresholds {
+    /// Grade a metric where larger values are worse
+    fn above(&self, value: f64) -> Verdict {
+        if value > self.error {
+            Verdict::Fail
+        } else if value > self.warn {
+            Verdict::Warn
+        } else {
+            Verdict::Pass
+        }
+    }
+
+    /// Grade a metric where smaller values are worse
+    fn below(&self, value: f64) -> Verdict {
+        if value < self.error {
+            Verdict::Fail
+        } else if value < self.warn {
+            Verdict::Warn
+        } else {
+            Verdict::Pass
+        }
+    }
+}
+
+/// Module thresholds, loaded from a FastQC-style limits file
+#[derive(Debug, Clone)]
+pub struct QcLimits {
+    pub quality_base_lower: Thresholds,  // Lowest per-cycle lower quartile
+    pub quality_base_median: Thresholds, // Lowest per-cycle median
+    pub n_content: Thresholds,           // Highest per-cycle N %
+    pub gc_sequence: Thresholds,         // % of reads deviating from the normal fit
+    pub duplication: Thresholds,         // % of reads remaining if deduplicated
+    pub adapter: Thresholds,             // Highest adapter content at any position (%)
+    pub overrepresented: Thresholds,     // Highest single overrepresented sequence (%)
+    pub ignored: HashSet<QcModule>,
+}
+
+impl Default for QcLimits {
+    fn default() -> Self {
+        Self {
+            quality_base_lower: Thresholds { warn: 10.0, error: 5.0 },
+            quality_base_median: Thresholds { warn: 25.0, error: 20.0 },
+            n_content: Thresholds { warn: 5.0, error: 20.0 },
+            gc_sequence: Thresholds { warn: 15.0, error: 30.0 },
+            duplication: Thresholds { warn: 70.0, error: 50.0 },
+            adapter: Thresholds { warn: 5.0, error: 10.0 },
+            overrepresented: Thresholds { warn: 0.1, error: 1.0 },
+            ignored: HashSet::new(),
+        }
+    }
+}
+
+impl QcLimits {
+    /// Load a limits file; keys not present keep their defaults
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read limits file: {}", path.as_ref().display()))?;
+        Self::parse(&content)
+    }
+
+    /// Parse limits in FastQC `limits.txt` format
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut limits = Self::default();
+
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [key, kind, value] = fields[..] else {
+                anyhow::bail!("Limits line {}: expected '<key> <warn|error|ignore> <value>'", line_number + 1);
+            };
+            let value: f64 = value
+                .parse()
+                .with_context(|| format!("Limits line {}: invalid value '{}'", line_number + 1, value))?;
+
+            if kind == "ignore" {
+                if let Some(module) = QcModule::ALL.iter().find(|module| module.limits_key() == key) {
+                    if value != 0.0 {
+                        limits.ignored.insert(*module);
+                    } else {
+                        limits.ignored.remove(module);
+                    }
+                }
+                continue;
+            }
+
+            let thresholds = match key {
+                "quality_base_lower" => &mut limits.quality_base_lower,
+                "quality_base_median" => &mut limits.quality_base_median,
+                "n_content" => &mut limits.n_content,
+                "gc_sequence" => &mut limits.gc_sequence,
+                "duplication" => &mut limits.duplication,
+                "adapter" => &mut limits.adapter,
+                "overrepresented" => &mut limits.overrepresented,
+                _ => continue,
+            };
+            match kind {
+                "warn" => thresholds.warn = value,
+                "error" => thresholds.error = value,
+                _ => anyhow::bail!("Limits line {}: unknown threshold type '{}'", line_number + 1, kind),
+            }
+        }
+
+        Ok(limits)
+    }
+
+    /// Grade every module that is not ignored
+    pub fn evaluate(&self, stats: &QcStats) -> Vec<ModuleVerdict> {
+        QcModule::ALL
+            .iter()
+            .filter(|module| !self.ignored.contains(module))
+            .filter_map(|&module| {
+                let (verdict, detail) = match module {
+                    QcModule::PerBaseQuality => self.per_base_quality(stats)?,
+                    QcModule::NContent => {
+                        let peak = stats.per_position_composition.iter().max_by(|a, b| a.n.total_cmp(&b.n))?;
+                        (
+                            self.n_content.above(peak.n),
+                            format!("{:.2}% N at cycle {}", peak.n, peak.position),
+                        )
+                    }
+                    QcModule::GcContent => {
+                        if stats.gc_distribution.reads == 0 {
+                            return None;
+                        }
+                        let deviation = stats.gc_distribution.deviation_from_normal;
+                        (
+                            self.gc_sequence.above(deviation),
+                            format!("{:.2}% of reads deviate from the normal fit", deviation),
+                        )
+                    }
+                    QcModule::Duplication => {
+                        if stats.duplication.reads_sampled == 0 {
+                            return None;
+                        }
+                        let remaining = stats.duplication.percent_remaining_if_deduplicated;
+                        (
+                            self.duplication.below(remaining),
+                            format!("{:.2}% of reads remain if deduplicated", remaining),
+                        )
+                    }
+                    QcModule::AdapterContent => {
+                        let worst = stats
+                            .adapter_content
+                            .iter()
+                            .max_by(|a, b| a.max_percent.total_cmp(&b.max_percent))?;
+                        (
+                            self.adapter.above(worst.max_percent),
+                            format!(
+                                "{} adapter in {:.2}% of reads by cycle {}",
+                                worst.adapter,
+                                worst.max_percent,
+                                worst.percent_by_position.len()
+                            ),
+                        )
+                    }
+                    QcModule::Overrepresented => {
+                        let top = stats
+                            .overrepresented_sequences
+                            .iter()
+                            .map(|hit| hit.percentage)
+                            .fold(0.0, f64::max);
+                        (
+                            self.overrepresented.above(top),
+                            format!("top sequence at {:.2}% of reads", top),
+                        )
+                    }
+                };
+                Some(ModuleVerdict { module, verdict, detail })
+            })
+            .collect()
+    }
+
+    /// Worst of the lower-quartile and median checks across all cycles
+    fn per_base_quality(&self, stats: &QcStats) -> Option<(Verdict, String)> {
+        let lowest_lower = stats
+            .per_position_quality
+            .iter()
+            .min_by(|a, b| a.lower_quartile.total_cmp(&b.lower_quartile))?;
+        let lowest_median = stats
+            .per_position_quality
+            .iter()
+            .min_by(|a, b| a.median.total_cmp(&b.median))?;
+
+        let verdict = self
+            .quality_base_lower
+            .below(lowest_lower.lower_quartile)
+            .max(self.quality_base_median.below(lowest_median.median));
+        let detail = format!(
+            "lowest lower quartile Q{:.0} at cycle {}, lowest median Q{:.0} at cycle {}",
+            lowest_lower.lower_quartile, lowest_lower.position, lowest_median.median, lowest_median.position
+        );
+        Some((verdict, detail))
+    }
+}
+
+/// Worst verdict across modules (PASS when none were evaluated)
+pub fn overall_verdict(verdicts: &[ModuleVerdict]) -> Verdict {
+    verdicts.iter().map(|v| v.verdict).max().unwrap_or(Verdict::Pass)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::QualityEncoding;
+    use crate::quality::QualityAccumulator;
+    use biometal::FastqRecord;
+
+    /// Random reads with the given GC probability (xorshift, so the sample is fixed)
+    fn add_random_reads(accumulator: &mut QualityAccumulator, reads: usize, length: usize, gc: f64, seed: &mut u64) {
+        for _ in 0..reads {
+            let sequence: Vec<u8> = (0..length)
+                .map(|_| {
+                    *seed ^= *seed << 13;
+                    *seed ^= *seed >> 7;
+                    *seed ^= *seed << 17;
+                    let draw = (*seed >> 11) as f64 / (1u64 << 53) as f64;
+                    let strong = draw < gc;
+                    match (strong, *seed & 1 == 0) {
+                        (true, true) => b'G',
+                        (true, false) => b'C',
+                        (false, true) => b'A',
+                        (false, false) => b'T',
+                    }
+                })
+                .collect();
+            accumulator.add(&FastqRecord::new("r".to_string(), sequence, vec![b'I'; length]));
+        }
+    }
+
+    #[test]
+    fn test_limits_file_overrides_and_ignores() {
+        let limits = QcLimits::parse(
+            "# custom limits\n\
+             duplication\twarn\t80\n\
+             n_content   error  10\n\
+             adapter ignore 1\n\
+             kmer warn 2\n",
+        )
+        .unwrap();
+
+        assert_eq!(limits.duplication, Thresholds { warn: 80.0, error: 50.0 });
+        assert_eq!(limits.n_content, Thresholds { warn: 5.0, error: 10.0 });
+        assert!(limits.ignored.contains(&QcModule::AdapterContent));
+        assert_eq!(limits.duplication.below(75.0), Verdict::Warn);
+        assert_eq!(limits.n_content.above(12.0), Verdict::Fail);
+        assert!(QcLimits::parse("duplication warn").is_err());
+    }
+
+    #[test]
+    fn test_single_gc_peak_passes() {
+        let gc_verdict = |stats: &QcStats| {
+            QcLimits::default()
+                .evaluate(stats)
+                .into_iter()
+                .find(|verdict| verdict.module == QcModule::GcContent)
+                .unwrap()
+                .verdict
+        };
+
+        // Ordinary 150bp and 75bp libraries around 45% GC
+        let mut seed = 0x2545_f491_4f6c_dd1d;
+        for length in [150, 75] {
+            let mut accumulator = QualityAccumulator::new("sample", QualityEncoding::Phred33, 1);
+            add_random_reads(&mut accumulator, 20_000, length, 0.45, &mut seed);
+            assert_eq!(gc_verdict(&accumulator.finish()), Verdict::Pass, "{}bp", length);
+        }
+
+        // A bacterial population at 65% GC pulls the profile off the normal fit
+        let mut accumulator = QualityAccumulator::new("sample", QualityEncoding::Phred33, 1);
+        add_random_reads(&mut accumulator, 12_000, 150, 0.45, &mut seed);
+        add_random_reads(&mut accumulator, 8_000, 150, 0.65, &mut seed);
+        assert_ne!(gc_verdict(&accumulator.finish()), Verdict::Pass);
+    }
+}
@@ -5,9 +5,11 @@
 //! - complexity: For sequence diversity assessment
 //! - base_counting: For composition patterns
 
+use crate::distributions::{GcDistribution, GcDistributionAccumulator};
+use crate::parallel::{accumulate_fastq, DEFAULT_BATCH_SIZE};
 use anyhow::Result;
-use biometal::io::{DataSource, FastqStream};
 use biometal::operations::{complexity_score, gc_content};
+use biometal::FastqRecord;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -19,6 +21,9 @@ pub struct VlpReport {
     pub complexity_diversity: f64,
     pub compositional_evenness: f64,
     pub vlp_success_score: f64,
+    /// Per-read GC histogram; a multimodal profile suggests bacterial or host carry-over
+    #[serde(default)]
+    pub gc_distribution: GcDistribution,
 }
 
 /// VLP assessor using composition-based metrics
@@ -26,6 +31,7 @@ pub struct VlpAssessor {
     pub min_complexity: f64,
     pub optimal_gc_range: (f64, f64),
     pub min_length: usize,
+    pub threads: usize,
 }
 
 impl Default for VlpAssessor {
@@ -34,6 +40,7 @@ impl Default for VlpAssessor {
             min_complexity: 0.7,
             optimal_gc_range: (0.35, 0.65), // Typical viral GC range
             min_length: 50,
+            threads: 1,
         }
     }
 }
@@ -44,6 +51,7 @@ impl VlpAssessor {
             min_complexity,
             optimal_gc_range,
             min_length,
+            threads: 1,
         }
     }
 
@@ -57,134 +65,179 @@ impl VlpAssessor {
             .unwrap_or("unknown")
             .to_string();
 
-        // Initialize metrics collection
-        let mut total_reads = 0u64;
-        let mut gc_values = Vec::new();
-        let mut complexity_values = Vec::new();
-        let mut base_counts = [0u64; 4]; // A, T, G, C
+        // Batches of records are processed on `threads` workers and merged in order
+        let accumulator = accumulate_fastq(&fastq_path, self.threads, DEFAULT_BATCH_SIZE, || {
+            self.accumulator(&sample_name)
+        })?;
 
-        // Create biometal data source and stream
-        let data_source = DataSource::from_path(&fastq_path);
-        let fastq_stream = FastqStream::new(data_source)?;
+        Ok(accumulator.finish())
+    }
 
-        // Process reads using biometal streaming
-        for record_result in fastq_stream {
-            let record = record_result?;
+    /// Empty accumulator using this assessor's settings
+    pub fn accumulator(&self, sample_name: &str) -> VlpAccumulator {
+        VlpAccumulator::new(sample_name, self.optimal_gc_range, self.min_length)
+    }
 
-            // Skip empty or short records
-            if record.is_empty() || record.sequence.len() < self.min_length {
-                continue;
-            }
+    /// Calculate overall VLP success score
+    pub fn calculate_success_score(
+        &self,
+        gc_score: f64,
+        complexity: f64,
+        evenness: f64,
+    ) -> f64 {
+        success_score(gc_score, complexity, evenness)
+    }
 
-            total_reads += 1;
-
-            // Use biometal gc_content primitive
-            let gc_ratio = gc_content(&record.sequence);
-            gc_values.push(gc_ratio);
-
-            // Use biometal complexity primitive
-            let complexity = complexity_score(&record.sequence);
-            complexity_values.push(complexity);
-
-            // Count individual bases for compositional evenness
-            for &base in &record.sequence {
-                match base {
-                    b'A' | b'a' => base_counts[0] += 1,
-                    b'T' | b't' => base_counts[1] += 1,
-                    b'G' | b'g' => base_counts[2] += 1,
-                    b'C' | b'c' => base_counts[3] += 1,
-                    _ => {}, // Ignore ambiguous bases
-                }
+    /// Determine if VLP preparation was successful
+    pub fn is_vlp_successful(&self, report: &VlpReport) -> bool {
+        report.vlp_success_score >= 0.7
+            && report.complexity_diversity >= self.min_complexity
+    }
+}
+
+/// Streaming VLP metrics that can be merged across shards
+///
+/// Per-read GC and complexity are kept as running sums rather than per-read
+/// values, so memory does not grow with the number of reads.
+#[derive(Debug, Clone)]
+pub struct VlpAccumulator {
+    pub sample_name: String,
+    pub optimal_gc_range: (f64, f64),
+    pub min_length: usize,
+    total_reads: u64,
+    gc_sum: f64,
+    gc_sum_squares: f64,
+    gc_in_range: u64,
+    complexity_sum: f64,
+    base_counts: [u64; 4], // A, T, G, C
+    gc_distribution: GcDistributionAccumulator,
+}
+
+impl VlpAccumulator {
+    pub fn new(sample_name: &str, optimal_gc_range: (f64, f64), min_length: usize) -> Self {
+        Self {
+            sample_name: sample_name.to_string(),
+            optimal_gc_range,
+            min_length,
+            total_reads: 0,
+            gc_sum: 0.0,
+            gc_sum_squares: 0.0,
+            gc_in_range: 0,
+            complexity_sum: 0.0,
+            base_counts: [0; 4],
+            gc_distribution: GcDistributionAccumulator::default(),
+        }
+    }
+
+    /// Add one record; empty reads and reads below `min_length` are skipped
+    pub fn add(&mut self, record: &FastqRecord) {
+        if record.is_empty() || record.sequence.len() < self.min_length {
+            return;
+        }
+
+        self.total_reads += 1;
+
+        // Use biometal gc_content primitive
+        let gc_ratio = gc_content(&record.sequence);
+        self.gc_sum += gc_ratio;
+        self.gc_sum_squares += gc_ratio * gc_ratio;
+        if gc_ratio >= self.optimal_gc_range.0 && gc_ratio <= self.optimal_gc_range.1 {
+            self.gc_in_range += 1;
+        }
+        self.gc_distribution.add(&record.sequence);
+
+        // Use biometal complexity primitive
+        self.complexity_sum += complexity_score(&record.sequence);
+
+        // Count individual bases for compositional evenness
+        for &base in &record.sequence {
+            match base {
+                b'A' | b'a' => self.base_counts[0] += 1,
+                b'T' | b't' => self.base_counts[1] += 1,
+                b'G' | b'g' => self.base_counts[2] += 1,
+                b'C' | b'c' => self.base_counts[3] += 1,
+                _ => {}, // Ignore ambiguous bases
             }
         }
+    }
 
-        // Calculate VLP success metrics
-        let gc_distribution_score = self.calculate_gc_distribution_score(&gc_values);
-        let complexity_diversity = if !complexity_values.is_empty() {
-            complexity_values.iter().sum::<f64>() / complexity_values.len() as f64
+    /// Fold in metrics from another shard
+    pub fn merge(&mut self, other: Self) {
+        self.total_reads += other.total_reads;
+        self.gc_sum += other.gc_sum;
+        self.gc_sum_squares += other.gc_sum_squares;
+        self.gc_in_range += other.gc_in_range;
+        self.complexity_sum += other.complexity_sum;
+        for (count, other) in self.base_counts.iter_mut().zip(other.base_counts) {
+            *count += other;
+        }
+        self.gc_distribution.merge(other.gc_distribution);
+    }
+
+    /// Calculate VLP success metrics
+    pub fn finish(&self) -> VlpReport {
+        let gc_distribution_score = self.gc_distribution_score();
+        let complexity_diversity = if self.total_reads > 0 {
+            self.complexity_sum / self.total_reads as f64
         } else {
             0.0
         };
-        let compositional_evenness = self.calculate_compositional_evenness(&base_counts);
-        let vlp_success_score = self.calculate_success_score(
-            gc_distribution_score,
-            complexity_diversity,
-            compositional_evenness,
-        );
+        let compositional_evenness = compositional_evenness(&self.base_counts);
+        let vlp_success_score = success_score(gc_distribution_score, complexity_diversity, compositional_evenness);
 
-        let report = VlpReport {
-            sample_name,
-            total_reads,
+        VlpReport {
+            sample_name: self.sample_name.clone(),
+            total_reads: self.total_reads,
             gc_distribution_score,
             complexity_diversity,
             compositional_evenness,
             vlp_success_score,
-        };
-
-        Ok(report)
+            gc_distribution: self.gc_distribution.finish(),
+        }
     }
 
-    /// Calculate GC distribution score based on diversity within optimal range
-    fn calculate_gc_distribution_score(&self, gc_values: &[f64]) -> f64 {
-        if gc_values.is_empty() {
+    /// GC distribution score based on diversity within optimal range
+    fn gc_distribution_score(&self) -> f64 {
+        if self.total_reads == 0 {
             return 0.0;
         }
 
-        // Calculate proportion of reads within optimal GC range
-        let in_range_count = gc_values
-            .iter()
-            .filter(|&&gc| gc >= self.optimal_gc_range.0 && gc <= self.optimal_gc_range.1)
-            .count();
-
-        let in_range_proportion = in_range_count as f64 / gc_values.len() as f64;
-
-        // Calculate GC diversity (standard deviation)
-        let mean_gc = gc_values.iter().sum::<f64>() / gc_values.len() as f64;
-        let variance = gc_values
-            .iter()
-            .map(|&gc| (gc - mean_gc).powi(2))
-            .sum::<f64>()
-            / gc_values.len() as f64;
+        let reads = self.total_reads as f64;
+
+        // Proportion of reads within optimal GC range
+        let in_range_proportion = self.gc_in_range as f64 / reads;
+
+        // GC diversity (standard deviation)
+        let mean_gc = self.gc_sum / reads;
+        let variance = (self.gc_sum_squares / reads - mean_gc * mean_gc).max(0.0);
         let std_dev = variance.sqrt();
 
         // Score combines range adherence with diversity (normalized std dev)
         (in_range_proportion * 0.7) + (std_dev.min(0.2) / 0.2 * 0.3)
     }
+}
 
-    /// Calculate compositional evenness using Shannon evenness index
-    fn calculate_compositional_evenness(&self, base_counts: &[u64; 4]) -> f64 {
-        let total_bases: u64 = base_counts.iter().sum();
-        if total_bases == 0 {
-            return 0.0;
-        }
+/// Compositional evenness using Shannon evenness index
+fn compositional_evenness(base_counts: &[u64; 4]) -> f64 {
+    let total_bases: u64 = base_counts.iter().sum();
+    if total_bases == 0 {
+        return 0.0;
+    }
 
-        // Calculate Shannon entropy
-        let mut entropy = 0.0;
-        for &count in base_counts {
-            if count > 0 {
-                let proportion = count as f64 / total_bases as f64;
-                entropy -= proportion * proportion.ln();
-            }
+    // Calculate Shannon entropy
+    let mut entropy = 0.0;
+    for &count in base_counts {
+        if count > 0 {
+            let proportion = count as f64 / total_bases as f64;
+            entropy -= proportion * proportion.ln();
         }
-
-        // Normalize by maximum possible entropy (ln(4) for 4 bases)
-        entropy / 4.0_f64.ln()
     }
 
-    /// Calculate overall VLP success score
-    pub fn calculate_success_score(
-        &self,
-        gc_score: f64,
-        complexity: f64,
-        evenness: f64,
-    ) -> f64 {
-        // Weighted combination of metrics
-        (gc_score * 0.3) + (complexity * 0.4) + (evenness * 0.3)
-    }
+    // Normalize by maximum possible entropy (ln(4) for 4 bases)
+    entropy / 4.0_f64.ln()
+}
 
-    /// Determine if VLP preparation was successful
-    pub fn is_vlp_successful(&self, report: &VlpReport) -> bool {
-        report.vlp_success_score >= 0.7
-            && report.complexity_diversity >= self.min_complexity
-    }
-}
\ No newline at end of file
+/// Weighted combination of metrics
+fn success_score(gc_score: f64, complexity: f64, evenness: f64) -> f64 {
+    (gc_score * 0.3) + (complexity * 0.4) + (evenness * 0.3)
+}